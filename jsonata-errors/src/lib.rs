@@ -41,6 +41,7 @@ pub enum Error {
     S0201SyntaxError(usize, String),
     S0202UnexpectedToken(usize, String, String),
     S0204UnknownOperator(usize, String),
+    S0207UnexpectedEndOfExpression(usize),
     S0203ExpectedTokenBeforeEnd(usize, String),
     S0208InvalidFunctionParam(usize, String),
     S0209InvalidPredicate(usize),
@@ -49,11 +50,42 @@ pub enum Error {
     S0212ExpectedVarLeft(usize),
     S0213InvalidStep(usize, String),
     S0214ExpectedVarRight(usize, String),
+    S0218UnknownFunctions(usize, String),
 
     // Runtime errors
     D1001NumberOfOutRange(f64),
     D1002NegatingNonNumeric(usize, String),
     D1009MultipleKeys(usize, String),
+    D2014RangeOutOfBounds(usize, usize, usize),
+    D3010EmptyPattern(usize),
+    D3011NegativeLimit(usize),
+    D3012InvalidReplacementType(usize),
+    D3020NegativeLimit(usize),
+    D3030NonNumericCast(usize, String),
+    D3050ReduceInsufficientArgs(usize),
+    D3061PowUnrepresentable(usize, f64, f64),
+    D3070InvalidDefaultSort(usize),
+    D3080FormatTooManySubPictures(usize),
+    D3081FormatMultipleDecimalSeparators(usize),
+    D3082FormatMultiplePercents(usize),
+    D3083FormatMultiplePerMilles(usize),
+    D3084FormatPercentAndPerMille(usize),
+    D3085FormatNoMantissaDigits(usize),
+    D3086FormatPassiveBetweenActive(usize),
+    D3087FormatGroupingNextToDecimal(usize),
+    D3088FormatGroupingAtEnd(usize),
+    D3089FormatAdjacentGroupings(usize),
+    D3090FormatDigitBeforeOptional(usize),
+    D3091FormatOptionalBeforeDigit(usize),
+    D3092FormatExponentWithPercent(usize),
+    D3093FormatInvalidExponent(usize),
+    D3100RadixOutOfRange(usize, f64),
+    D3130FormatSequenceUnsupported(usize, String),
+    D3131FormatMixedDigitGroups(usize),
+    D3137Error(usize, String),
+    D3138SingleTooMany(usize),
+    D3139SingleTooFew(usize),
+    D3141Assert(usize, String),
 
     // Type errors
     T0410ArgumentNotValid(usize, usize, String),
@@ -61,12 +93,19 @@ pub enum Error {
     T1003NonStringKey(usize, String),
     T1005InvokedNonFunctionSuggest(usize, String),
     T1006InvokedNonFunction(usize),
+    T1007InvokedNonFunctionPartialSuggest(usize, String),
+    T1008InvokedNonFunctionPartial(usize),
     T2001LeftSideNotNumber(usize, String),
     T2002RightSideNotNumber(usize, String),
     T2003LeftSideNotInteger(usize),
     T2004RightSideNotInteger(usize),
+    T2006RightSideNotFunction(usize),
+    T2007SortMismatch(usize, String, String),
+    T2008SortNotNumberOrString(usize),
     T2009BinaryOpMismatch(usize, String, String, String),
     T2010BinaryOpTypes(usize, String),
+    T2011UpdateNotObject(usize, String),
+    T2012DeleteNotStrings(usize, String),
 }
 
 impl error::Error for Error {}
@@ -124,6 +163,7 @@ impl Error {
             Error::S0202UnexpectedToken(..) => "S0202",
             Error::S0203ExpectedTokenBeforeEnd(..) => "S0203",
             Error::S0204UnknownOperator(..) => "S0204",
+            Error::S0207UnexpectedEndOfExpression(..) => "S0207",
             Error::S0208InvalidFunctionParam(..) => "S0208",
             Error::S0209InvalidPredicate(..) => "S0209",
             Error::S0210MultipleGroupBy(..) => "S0210",
@@ -131,11 +171,42 @@ impl Error {
             Error::S0212ExpectedVarLeft(..) => "S0212",
             Error::S0213InvalidStep(..) => "S0213",
             Error::S0214ExpectedVarRight(..) => "S0214",
+            Error::S0218UnknownFunctions(..) => "S0218",
 
             // Runtime errors
             Error::D1001NumberOfOutRange(..) => "D1001",
             Error::D1002NegatingNonNumeric(..) => "D1002",
             Error::D1009MultipleKeys(..) => "D1009",
+            Error::D2014RangeOutOfBounds(..) => "D2014",
+            Error::D3010EmptyPattern(..) => "D3010",
+            Error::D3011NegativeLimit(..) => "D3011",
+            Error::D3012InvalidReplacementType(..) => "D3012",
+            Error::D3020NegativeLimit(..) => "D3020",
+            Error::D3030NonNumericCast(..) => "D3030",
+            Error::D3050ReduceInsufficientArgs(..) => "D3050",
+            Error::D3061PowUnrepresentable(..) => "D3061",
+            Error::D3070InvalidDefaultSort(..) => "D3070",
+            Error::D3080FormatTooManySubPictures(..) => "D3080",
+            Error::D3081FormatMultipleDecimalSeparators(..) => "D3081",
+            Error::D3082FormatMultiplePercents(..) => "D3082",
+            Error::D3083FormatMultiplePerMilles(..) => "D3083",
+            Error::D3084FormatPercentAndPerMille(..) => "D3084",
+            Error::D3085FormatNoMantissaDigits(..) => "D3085",
+            Error::D3086FormatPassiveBetweenActive(..) => "D3086",
+            Error::D3087FormatGroupingNextToDecimal(..) => "D3087",
+            Error::D3088FormatGroupingAtEnd(..) => "D3088",
+            Error::D3089FormatAdjacentGroupings(..) => "D3089",
+            Error::D3090FormatDigitBeforeOptional(..) => "D3090",
+            Error::D3091FormatOptionalBeforeDigit(..) => "D3091",
+            Error::D3092FormatExponentWithPercent(..) => "D3092",
+            Error::D3093FormatInvalidExponent(..) => "D3093",
+            Error::D3100RadixOutOfRange(..) => "D3100",
+            Error::D3130FormatSequenceUnsupported(..) => "D3130",
+            Error::D3131FormatMixedDigitGroups(..) => "D3131",
+            Error::D3137Error(..) => "D3137",
+            Error::D3138SingleTooMany(..) => "D3138",
+            Error::D3139SingleTooFew(..) => "D3139",
+            Error::D3141Assert(..) => "D3141",
 
             // Type errors
             Error::T0410ArgumentNotValid(..) => "T0410",
@@ -143,12 +214,115 @@ impl Error {
             Error::T1003NonStringKey(..) => "T1003",
             Error::T1005InvokedNonFunctionSuggest(..) => "T1005",
             Error::T1006InvokedNonFunction(..) => "T1006",
+            Error::T1007InvokedNonFunctionPartialSuggest(..) => "T1007",
+            Error::T1008InvokedNonFunctionPartial(..) => "T1008",
             Error::T2001LeftSideNotNumber(..) => "T2001",
             Error::T2002RightSideNotNumber(..) => "T2002",
             Error::T2003LeftSideNotInteger(..) => "T2003",
             Error::T2004RightSideNotInteger(..) => "T2004",
+            Error::T2006RightSideNotFunction(..) => "T2006",
+            Error::T2007SortMismatch(..) => "T2007",
+            Error::T2008SortNotNumberOrString(..) => "T2008",
             Error::T2009BinaryOpMismatch(..) => "T2009",
             Error::T2010BinaryOpTypes(..) => "T2010",
+            Error::T2011UpdateNotObject(..) => "T2011",
+            Error::T2012DeleteNotStrings(..) => "T2012",
+        }
+    }
+
+    /// The char index in the expression that the error was raised at. Errors parsing the JSON
+    /// input or a function signature, and numeric overflow, aren't tied to a position.
+    pub fn position(&self) -> Option<usize> {
+        match *self {
+            Error::I0201UnexpectedCharacter { .. }
+            | Error::I0202UnexpectedEndOfJson
+            | Error::I0203ExceededDepthLimit
+            | Error::I0204FailedUtf8Parsing
+            | Error::I0205WrongType(..)
+            | Error::F0401UnexpectedEndOfSignature
+            | Error::F0402SignatureStartInvalid
+            | Error::F0403SignatureEndInvalid
+            | Error::F0404UnexpectedCharsAtEndOfSignature
+            | Error::F0405OptionalShouldComeAfterType
+            | Error::F0406AllowContextShouldComeAfterType
+            | Error::F0407OneOrMoreShouldComeAfterType
+            | Error::F0408NoTypeBetweenCarets
+            | Error::F0409MultipleTypesInArray
+            | Error::F0410UnterminatedCaret
+            | Error::F0411NoTypeBetweenParens
+            | Error::F0412UnterminatedParen
+            | Error::F0413ExpectedInSignature(..)
+            | Error::F0414UnexpectedCharInSignature(..)
+            | Error::D1001NumberOfOutRange(..) => None,
+            Error::S0101UnterminatedStringLiteral(p)
+            | Error::S0102LexedNumberOutOfRange(p, ..)
+            | Error::S0103UnsupportedEscape(p, ..)
+            | Error::S0104InvalidUnicodeEscape(p)
+            | Error::S0105UnterminatedQuoteProp(p)
+            | Error::S0106UnterminatedComment(p)
+            | Error::S0201SyntaxError(p, ..)
+            | Error::S0202UnexpectedToken(p, ..)
+            | Error::S0204UnknownOperator(p, ..)
+            | Error::S0207UnexpectedEndOfExpression(p)
+            | Error::S0203ExpectedTokenBeforeEnd(p, ..)
+            | Error::S0208InvalidFunctionParam(p, ..)
+            | Error::S0209InvalidPredicate(p)
+            | Error::S0210MultipleGroupBy(p)
+            | Error::S0211InvalidUnary(p, ..)
+            | Error::S0212ExpectedVarLeft(p)
+            | Error::S0213InvalidStep(p, ..)
+            | Error::S0214ExpectedVarRight(p, ..)
+            | Error::S0218UnknownFunctions(p, ..)
+            | Error::D1002NegatingNonNumeric(p, ..)
+            | Error::D1009MultipleKeys(p, ..)
+            | Error::D2014RangeOutOfBounds(p, ..)
+            | Error::D3010EmptyPattern(p)
+            | Error::D3011NegativeLimit(p)
+            | Error::D3012InvalidReplacementType(p)
+            | Error::D3020NegativeLimit(p)
+            | Error::D3030NonNumericCast(p, ..)
+            | Error::D3050ReduceInsufficientArgs(p)
+            | Error::D3061PowUnrepresentable(p, ..)
+            | Error::D3070InvalidDefaultSort(p)
+            | Error::D3080FormatTooManySubPictures(p)
+            | Error::D3081FormatMultipleDecimalSeparators(p)
+            | Error::D3082FormatMultiplePercents(p)
+            | Error::D3083FormatMultiplePerMilles(p)
+            | Error::D3084FormatPercentAndPerMille(p)
+            | Error::D3085FormatNoMantissaDigits(p)
+            | Error::D3086FormatPassiveBetweenActive(p)
+            | Error::D3087FormatGroupingNextToDecimal(p)
+            | Error::D3088FormatGroupingAtEnd(p)
+            | Error::D3089FormatAdjacentGroupings(p)
+            | Error::D3090FormatDigitBeforeOptional(p)
+            | Error::D3091FormatOptionalBeforeDigit(p)
+            | Error::D3092FormatExponentWithPercent(p)
+            | Error::D3093FormatInvalidExponent(p)
+            | Error::D3100RadixOutOfRange(p, ..)
+            | Error::D3130FormatSequenceUnsupported(p, ..)
+            | Error::D3131FormatMixedDigitGroups(p)
+            | Error::D3137Error(p, ..)
+            | Error::D3138SingleTooMany(p)
+            | Error::D3139SingleTooFew(p)
+            | Error::D3141Assert(p, ..)
+            | Error::T0410ArgumentNotValid(p, ..)
+            | Error::T0412ArgumentMustBeArrayOfType(p, ..)
+            | Error::T1003NonStringKey(p, ..)
+            | Error::T1005InvokedNonFunctionSuggest(p, ..)
+            | Error::T1006InvokedNonFunction(p)
+            | Error::T1007InvokedNonFunctionPartialSuggest(p, ..)
+            | Error::T1008InvokedNonFunctionPartial(p)
+            | Error::T2001LeftSideNotNumber(p, ..)
+            | Error::T2002RightSideNotNumber(p, ..)
+            | Error::T2003LeftSideNotInteger(p)
+            | Error::T2004RightSideNotInteger(p)
+            | Error::T2006RightSideNotFunction(p)
+            | Error::T2007SortMismatch(p, ..)
+            | Error::T2008SortNotNumberOrString(p)
+            | Error::T2009BinaryOpMismatch(p, ..)
+            | Error::T2010BinaryOpTypes(p, ..)
+            | Error::T2011UpdateNotObject(p, ..)
+            | Error::T2012DeleteNotStrings(p, ..) => Some(p),
         }
     }
 }  
@@ -224,6 +398,8 @@ impl fmt::Display for Error {
                 write!(f, "{}: Expected `{}` before end of expression", p, t),
             S0204UnknownOperator(ref p, ref t) =>
                 write!(f, "{}: Unknown operator: `{}`", p, t),
+            S0207UnexpectedEndOfExpression(ref p) =>
+                write!(f, "{}: Unexpected end of expression", p),
             S0208InvalidFunctionParam(ref p, ref k) =>
                 write!(f, "{}: Parameter `{}` of function definition must be a variable name (start with $)", p, k),
             S0209InvalidPredicate(ref p) =>
@@ -238,6 +414,8 @@ impl fmt::Display for Error {
                 write!(f, "{}: The literal value `{}` cannot be used as a step within a path expression", p, k),
             S0214ExpectedVarRight(ref p, ref k) =>
                 write!(f, "{}: The right side of `{}` must be a variable name (start with $)", p, k),
+            S0218UnknownFunctions(ref p, ref n) =>
+                write!(f, "{}: The expression calls functions that aren't defined: {}", p, n),
             
             // Runtime errors
             D1001NumberOfOutRange(ref n) =>
@@ -246,6 +424,66 @@ impl fmt::Display for Error {
                 write!(f, "{}: Cannot negate a non-numeric value `{}`", p, v),
             D1009MultipleKeys(ref p, ref k) =>
                 write!( f, "{}: Multiple key definitions evaluate to same key: {}", p, k),
+            D2014RangeOutOfBounds(ref p, ref l, ref s) =>
+                write!(f, "{}: The size of the sequence allocated by the range operator (..) must not exceed {}.  Attempted to allocate {}", p, l, s),
+            D3010EmptyPattern(ref p) =>
+                write!(f, "{}: Second argument of replace function cannot be an empty string", p),
+            D3011NegativeLimit(ref p) =>
+                write!(f, "{}: Fourth argument of replace function must evaluate to a positive number", p),
+            D3012InvalidReplacementType(ref p) =>
+                write!(f, "{}: Attempted to replace a matched string with a non-string value", p),
+            D3020NegativeLimit(ref p) =>
+                write!(f, "{}: Third argument of split function must evaluate to a positive number", p),
+            D3030NonNumericCast(ref p, ref v) =>
+                write!(f, "{}: Unable to cast value to a number: {}", p, v),
+            D3050ReduceInsufficientArgs(ref p) =>
+                write!(f, "{}: The second argument of reduce function must be a function with at least two arguments", p),
+            D3061PowUnrepresentable(ref p, ref b, ref e) =>
+                write!(f, "{}: The power function has resulted in a value that cannot be represented as a JSON number: base={}, exponent={}", p, b, e),
+            D3070InvalidDefaultSort(ref p) =>
+                write!(f, "{}: The single argument form of the sort function can only be applied to an array of strings or an array of numbers.  Use the second argument to specify a comparison function", p),
+            D3080FormatTooManySubPictures(ref p) =>
+                write!(f, "{}: The picture string must only contain a maximum of two sub-pictures", p),
+            D3081FormatMultipleDecimalSeparators(ref p) =>
+                write!(f, "{}: The sub-picture must not contain more than one instance of the 'decimal-separator' character", p),
+            D3082FormatMultiplePercents(ref p) =>
+                write!(f, "{}: The sub-picture must not contain more than one instance of the 'percent' character", p),
+            D3083FormatMultiplePerMilles(ref p) =>
+                write!(f, "{}: The sub-picture must not contain more than one instance of the 'per-mille' character", p),
+            D3084FormatPercentAndPerMille(ref p) =>
+                write!(f, "{}: The sub-picture must not contain both a 'percent' and a 'per-mille' character", p),
+            D3085FormatNoMantissaDigits(ref p) =>
+                write!(f, "{}: The mantissa part of a sub-picture must contain at least one character that is either an 'optional digit character' or a member of the 'decimal digit family'", p),
+            D3086FormatPassiveBetweenActive(ref p) =>
+                write!(f, "{}: The sub-picture must not contain a passive character that is preceded by an active character and that is followed by another active character", p),
+            D3087FormatGroupingNextToDecimal(ref p) =>
+                write!(f, "{}: The sub-picture must not contain a 'grouping-separator' character that appears adjacent to a 'decimal-separator' character", p),
+            D3088FormatGroupingAtEnd(ref p) =>
+                write!(f, "{}: The sub-picture must not contain a 'grouping-separator' at the end of the integer part", p),
+            D3089FormatAdjacentGroupings(ref p) =>
+                write!(f, "{}: The sub-picture must not contain two adjacent instances of the 'grouping-separator' character", p),
+            D3090FormatDigitBeforeOptional(ref p) =>
+                write!(f, "{}: The integer part of the sub-picture must not contain a member of the 'decimal digit family' that is followed by an instance of the 'optional digit character'", p),
+            D3091FormatOptionalBeforeDigit(ref p) =>
+                write!(f, "{}: The fractional part of the sub-picture must not contain an instance of the 'optional digit character' that is followed by a member of the 'decimal digit family'", p),
+            D3092FormatExponentWithPercent(ref p) =>
+                write!(f, "{}: A sub-picture that contains a 'percent' or 'per-mille' character must not contain a character treated as an 'exponent-separator'", p),
+            D3093FormatInvalidExponent(ref p) =>
+                write!(f, "{}: The exponent part of the sub-picture must comprise only of one or more characters that are members of the 'decimal digit family'", p),
+            D3100RadixOutOfRange(ref p, ref r) =>
+                write!(f, "{}: The radix of the formatBase function must be between 2 and 36.  It was given {}", p, r),
+            D3130FormatSequenceUnsupported(ref p, ref v) =>
+                write!(f, "{}: Formatting or parsing an integer as a sequence starting with {} is not supported by this implementation", p, v),
+            D3131FormatMixedDigitGroups(ref p) =>
+                write!(f, "{}: In a decimal digit pattern, all digits must be from the same decimal group", p),
+            D3137Error(ref p, ref m) =>
+                write!(f, "{}: {}", p, m),
+            D3138SingleTooMany(ref p) =>
+                write!(f, "{}: The $single() function expected exactly 1 matching result.  Instead it matched more.", p),
+            D3139SingleTooFew(ref p) =>
+                write!(f, "{}: The $single() function expected exactly 1 matching result.  Instead it matched 0.", p),
+            D3141Assert(ref p, ref m) =>
+                write!(f, "{}: {}", p, m),
             
             // Type errors
             T0410ArgumentNotValid(ref p, ref i, ref t) =>
@@ -258,6 +496,10 @@ impl fmt::Display for Error {
                 write!(f, "{}: Attempted to invoke a non-function. Did you mean ${}?", p, t),
             T1006InvokedNonFunction(ref p) =>
                 write!(f, "{}: Attempted to invoke a non-function", p),
+            T1007InvokedNonFunctionPartialSuggest(ref p, ref t) =>
+                write!(f, "{}: Attempted to partially apply a non-function. Did you mean ${}?", p, t),
+            T1008InvokedNonFunctionPartial(ref p) =>
+                write!(f, "{}: Attempted to partially apply a non-function", p),
             T2001LeftSideNotNumber(ref p, ref o) =>
                 write!( f, "{}: The left side of the `{}` operator must evaluate to a number", p, o),
             T2002RightSideNotNumber(ref p, ref o) =>
@@ -266,17 +508,26 @@ impl fmt::Display for Error {
                 write!(f, "{}: The left side of the range operator (..) must evaluate to an integer", p),
             T2004RightSideNotInteger(ref p) =>
                 write!(f, "{}: The right side of the range operator (..) must evaluate to an integer", p),
+            T2006RightSideNotFunction(ref p) =>
+                write!(f, "{}: The right side of the function application operator ~> must be a function", p),
+            T2007SortMismatch(ref p, ref a, ref b) =>
+                write!(f, "{}: Type mismatch when comparing values {} and {} in order-by clause", p, a, b),
+            T2008SortNotNumberOrString(ref p) =>
+                write!(f, "{}: The expressions within an order-by clause must evaluate to numeric or string values", p),
             T2009BinaryOpMismatch(ref p,ref l ,ref r ,ref o ) =>
                 write!(f, "{}: The values {} and {} either side of operator {} must be of the same data type", p, l, r, o),
             T2010BinaryOpTypes(ref p, ref o) =>
                 write!(f, "{}: The expressions either side of operator `{}` must evaluate to numeric or string values", p, o),
+            T2011UpdateNotObject(ref p, ref v) =>
+                write!(f, "{}: The insert/update clause of the transform expression must evaluate to an object: {}", p, v),
+            T2012DeleteNotStrings(ref p, ref v) =>
+                write!(f, "{}: The delete clause of the transform expression must evaluate to a string or array of strings: {}", p, v),
         }
     }
 }
 
 // "S0205": "Unexpected token: {{token}}",
 // "S0206": "Unknown expression type: {{token}}",
-// "S0207": "Unexpected end of expression",
 // "S0215": "A context variable binding must precede any predicates on a step",
 // "S0216": "A context variable binding must precede the 'order-by' clause on a step",
 // "S0217": "The object representing the 'parent' cannot be derived from this expression",
@@ -288,59 +539,18 @@ impl fmt::Display for Error {
 // "S0500": "Attempted to evaluate an expression containing syntax error(s)",
 // "T0411": "Context value is not a compatible type with argument {{index}} of function {{token}}",
 // "D1004": "Regular expression matches zero length string",
-// "T1007": "Attempted to partially apply a non-function. Did you mean ${{{token}}}?",
-// "T1008": "Attempted to partially apply a non-function",
 // // "T1010": "The matcher function argument passed to function {{token}} does not return the correct object structure",
 // "D2005": "The left side of := must be a variable name (start with $)",  // defunct - replaced by S0212 parser error
-// "T2006": "The right side of the function application operator ~> must be a function",
-// "T2007": "Type mismatch when comparing values {{value}} and {{value2}} in order-by clause",
-// "T2008": "The expressions within an order-by clause must evaluate to numeric or string values",
-// "T2011": "The insert/update clause of the transform expression must evaluate to an object: {{value}}",
-// "T2012": "The delete clause of the transform expression must evaluate to a string or array of strings: {{value}}",
 // "T2013": "The transform expression clones the input object using the $clone() function.  This has been overridden in the current scope by a non-function.",
-// define_error!(
-//     D2014,
-//     "The size of the sequence allocated by the range operator (..) must not exceed 1e7.  Attempted to allocate {}",
-//     value
-// );
 // "D3001": "Attempting to invoke string function on Infinity or NaN",
-// "D3010": "Second argument of replace function cannot be an empty string",
-// "D3011": "Fourth argument of replace function must evaluate to a positive number",
-// "D3012": "Attempted to replace a matched string with a non-string value",
-// "D3020": "Third argument of split function must evaluate to a positive number",
-// "D3030": "Unable to cast value to a number: {{value}}",
 // "D3040": "Third argument of match function must evaluate to a positive number",
-// "D3050": "The second argument of reduce function must be a function with at least two arguments",
 // "D3060": "The sqrt function cannot be applied to a negative number: {{value}}",
-// "D3061": "The power function has resulted in a value that cannot be represented as a JSON number: base={{value}}, exponent={{exp}}",
-// "D3070": "The single argument form of the sort function can only be applied to an array of strings or an array of numbers.  Use the second argument to specify a comparison function",
-// "D3080": "The picture string must only contain a maximum of two sub-pictures",
-// "D3081": "The sub-picture must not contain more than one instance of the 'decimal-separator' character",
-// "D3082": "The sub-picture must not contain more than one instance of the 'percent' character",
-// "D3083": "The sub-picture must not contain more than one instance of the 'per-mille' character",
-// "D3084": "The sub-picture must not contain both a 'percent' and a 'per-mille' character",
-// "D3085": "The mantissa part of a sub-picture must contain at least one character that is either an 'optional digit character' or a member of the 'decimal digit family'",
-// "D3086": "The sub-picture must not contain a passive character that is preceded by an active character and that is followed by another active character",
-// "D3087": "The sub-picture must not contain a 'grouping-separator' character that appears adjacent to a 'decimal-separator' character",
-// "D3088": "The sub-picture must not contain a 'grouping-separator' at the end of the integer part",
-// "D3089": "The sub-picture must not contain two adjacent instances of the 'grouping-separator' character",
-// "D3090": "The integer part of the sub-picture must not contain a member of the 'decimal digit family' that is followed by an instance of the 'optional digit character'",
-// "D3091": "The fractional part of the sub-picture must not contain an instance of the 'optional digit character' that is followed by a member of the 'decimal digit family'",
-// "D3092": "A sub-picture that contains a 'percent' or 'per-mille' character must not contain a character treated as an 'exponent-separator'",
-// "D3093": "The exponent part of the sub-picture must comprise only of one or more characters that are members of the 'decimal digit family'",
-// "D3100": "The radix of the formatBase function must be between 2 and 36.  It was given {{value}}",
 // "D3110": "The argument of the toMillis function must be an ISO 8601 formatted timestamp. Given {{value}}",
 // "D3120": "Syntax error in expression passed to function eval: {{value}}",
 // "D3121": "Dynamic error evaluating the expression passed to function eval: {{value}}",
-// "D3130": "Formatting or parsing an integer as a sequence starting with {{value}} is not supported by this implementation",
-// "D3131": "In a decimal digit pattern, all digits must be from the same decimal group",
 // "D3132": "Unknown component specifier {{value}} in date/time picture string",
 // "D3133": "The 'name' modifier can only be applied to months and days in the date/time picture string, not {{value}}",
 // "D3134": "The timezone integer format specifier cannot have more than four digits",
 // "D3135": "No matching closing bracket ']' in date/time picture string",
 // "D3136": "The date/time picture string is missing specifiers required to parse the timestamp",
-// "D3137": "{{{message}}}",
-// "D3138": "The $single() function expected exactly 1 matching result.  Instead it matched more.",
-// "D3139": "The $single() function expected exactly 1 matching result.  Instead it matched 0.",
 // "D3140": "Malformed URL passed to ${{{functionName}}}(): {{value}}",
-// "D3141": "{{{message}}}"
\ No newline at end of file
@@ -20,13 +20,16 @@ pub fn signature(attr: TokenStream, item: TokenStream) -> TokenStream {
     };
 
     let sig_args_len = sig_args.len();
-    let fn_name = item.sig.ident.to_string();
+    let fn_name = item.sig.ident.clone();
     let fn_vis = item.vis.clone();
-    let sig_name = quote::format_ident!("__{}__signature", fn_name);
 
+    // The signature goes in a module named after the function, which lives in the type namespace
+    // alongside it, so it can be found from the function's name as `fn_name::SIGNATURE`
     let result = quote::quote! {
-        lazy_static::lazy_static! {
-            #fn_vis static ref #sig_name: [jsonata_signatures::Arg; #sig_args_len] = [ #(#sig_args, )* ];
+        #fn_vis mod #fn_name {
+            lazy_static::lazy_static! {
+                pub static ref SIGNATURE: [jsonata_signatures::Arg; #sig_args_len] = [ #(#sig_args, )* ];
+            }
         }
         #item
     };
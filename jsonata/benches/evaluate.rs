@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use bumpalo::Bump;
+use jsonata::{json, JsonAta};
+
+/// Builds a document of `items`, each with a nested array so that path steps have results to
+/// flatten.
+fn input(items: usize) -> String {
+    let items = (0..items)
+        .map(|i| {
+            format!(
+                r#"{{ "name": "item{}", "price": {}, "tags": ["a{}", "b{}"], "owner": {{ "id": {} }} }}"#,
+                i,
+                i % 100,
+                i,
+                i,
+                i % 10
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!(r#"{{ "items": [{}] }}"#, items)
+}
+
+fn evaluate(c: &mut Criterion) {
+    let input = input(500);
+
+    let expressions = [
+        ("field", "items.name"),
+        ("nested field", "items.owner.id"),
+        ("flatten", "items.tags"),
+        ("predicate", "items[price > 50].name"),
+        ("aggregate", "$sum(items.price)"),
+        ("count", "$count(items)"),
+        (
+            "object constructor",
+            "items.{ \"name\": name, \"price\": price }",
+        ),
+    ];
+
+    // Values own heap allocations that aren't released when the arena is dropped, so memory grows
+    // with every iteration. The number of iterations is kept down to bound it.
+    let mut group = c.benchmark_group("evaluate");
+    group.sample_size(10);
+    group.warm_up_time(Duration::from_millis(200));
+    group.measurement_time(Duration::from_secs(1));
+    for (name, expr) in expressions {
+        group.bench_with_input(BenchmarkId::from_parameter(name), &expr, |b, expr| {
+            // Each evaluation allocates into the instance's arena, so a fresh instance is used for
+            // every iteration rather than letting a single arena grow
+            b.iter(|| {
+                let jsonata = JsonAta::new(expr).unwrap();
+                black_box(jsonata.evaluate(Some(&input)).unwrap().is_undefined());
+            });
+        });
+    }
+    group.finish();
+}
+
+fn shallow_access(c: &mut Criterion) {
+    let input = input(5000);
+    let expr = "items[0].name";
+
+    let mut group = c.benchmark_group("shallow access");
+    group.sample_size(10);
+    group.warm_up_time(Duration::from_millis(200));
+    group.measurement_time(Duration::from_secs(1));
+
+    // The whole document is parsed on every evaluation, even though only one field is read
+    group.bench_function("parse per evaluation", |b| {
+        b.iter(|| {
+            let jsonata = JsonAta::new(expr).unwrap();
+            black_box(jsonata.evaluate(Some(&input)).unwrap().is_undefined());
+        });
+    });
+
+    // The document is parsed once up front and borrowed by every evaluation. The borrowed input
+    // has to outlive the instance, so a single instance is reused; a shallow expression only
+    // allocates a handful of values into its arena per evaluation.
+    let arena = Bump::new();
+    let document = json::parse(&input, &arena).unwrap();
+    let jsonata = JsonAta::new(expr).unwrap();
+    group.bench_function("borrowed input", |b| {
+        b.iter(|| {
+            black_box(jsonata.evaluate_value(document).unwrap().is_undefined());
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, evaluate, shallow_access);
+criterion_main!(benches);
@@ -1,3 +1,4 @@
+mod print;
 mod process;
 
 use jsonata_signatures::Arg;
@@ -161,4 +162,81 @@ impl Ast {
             stages: None,
         }
     }
+
+    /// Calls `f` with this node and then every node below it, including predicates, stages and
+    /// group by expressions.
+    pub fn walk<F: FnMut(&Ast)>(&self, f: &mut F) {
+        f(self);
+
+        match self.kind {
+            AstKind::Unary(UnaryOp::Minus(ref expr)) | AstKind::Filter(ref expr) => expr.walk(f),
+            AstKind::Unary(UnaryOp::ArrayConstructor(ref exprs))
+            | AstKind::Block(ref exprs)
+            | AstKind::Path(ref exprs) => exprs.iter().for_each(|expr| expr.walk(f)),
+            AstKind::Unary(UnaryOp::ObjectConstructor(ref object)) => walk_object(object, f),
+            AstKind::Binary(_, ref lhs, ref rhs) => {
+                lhs.walk(f);
+                rhs.walk(f);
+            }
+            AstKind::GroupBy(ref expr, ref object) => {
+                expr.walk(f);
+                walk_object(object, f);
+            }
+            AstKind::OrderBy(ref expr, ref terms) => {
+                expr.walk(f);
+                terms.iter().for_each(|(term, _)| term.walk(f));
+            }
+            AstKind::Sort(ref terms) => terms.iter().for_each(|(term, _)| term.walk(f)),
+            AstKind::Function {
+                ref proc, ref args, ..
+            } => {
+                proc.walk(f);
+                args.iter().for_each(|arg| arg.walk(f));
+            }
+            AstKind::Lambda {
+                ref args, ref body, ..
+            } => {
+                args.iter().for_each(|arg| arg.walk(f));
+                body.walk(f);
+            }
+            AstKind::Ternary {
+                ref cond,
+                ref truthy,
+                ref falsy,
+            } => {
+                cond.walk(f);
+                truthy.walk(f);
+                if let Some(falsy) = falsy {
+                    falsy.walk(f);
+                }
+            }
+            AstKind::Transform {
+                ref pattern,
+                ref update,
+                ref delete,
+            } => {
+                pattern.walk(f);
+                update.walk(f);
+                if let Some(delete) = delete {
+                    delete.walk(f);
+                }
+            }
+            _ => {}
+        }
+
+        if let Some((_, ref object)) = self.group_by {
+            walk_object(object, f);
+        }
+
+        for exprs in [&self.predicates, &self.stages].into_iter().flatten() {
+            exprs.iter().for_each(|expr| expr.walk(f));
+        }
+    }
+}
+
+fn walk_object<F: FnMut(&Ast)>(object: &Object, f: &mut F) {
+    for (key, value) in object {
+        key.walk(f);
+        value.walk(f);
+    }
 }
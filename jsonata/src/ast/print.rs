@@ -0,0 +1,435 @@
+use jsonata_signatures::{Arg, ArgKind, Flags};
+
+use super::*;
+
+// Precedence of expressions that are complete in themselves (literals, names, variables,
+// constructors, blocks and lambdas), which never need to be wrapped in parentheses
+const PRIMARY: u32 = 100;
+
+// Precedence of postfix expressions, i.e. function calls and predicates
+const POSTFIX: u32 = 80;
+
+// Precedence of a path, as both sides of the `.` operator bind at 75
+const PATH: u32 = 75;
+
+// Precedence of a group by, and of the operand of a unary minus
+const GROUP_BY: u32 = 70;
+
+impl Ast {
+    /// Renders the processed AST back into a canonical JSONata expression, using the fewest
+    /// parentheses needed for it to parse back into an equivalent AST.
+    pub fn to_expression_string(&self) -> String {
+        let mut out = String::new();
+        write_node(self, &mut out);
+        out
+    }
+}
+
+fn binary_precedence(op: &BinaryOp) -> u32 {
+    match op {
+        BinaryOp::Bind => 10,
+        BinaryOp::Or => 25,
+        BinaryOp::And => 30,
+        BinaryOp::Equal
+        | BinaryOp::NotEqual
+        | BinaryOp::LessThan
+        | BinaryOp::GreaterThan
+        | BinaryOp::LessThanEqual
+        | BinaryOp::GreaterThanEqual
+        | BinaryOp::In
+        | BinaryOp::Apply => 40,
+        BinaryOp::Add | BinaryOp::Subtract | BinaryOp::Concat => 50,
+        BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::Modulus => 60,
+        BinaryOp::Map => PATH,
+        BinaryOp::Predicate | BinaryOp::ContextBind | BinaryOp::PositionalBind => POSTFIX,
+
+        // Ranges only appear directly inside array constructors
+        BinaryOp::Range => 0,
+    }
+}
+
+// The precedence of the node without any of its predicates or group by
+fn base_precedence(node: &Ast) -> u32 {
+    match node.kind {
+        AstKind::Number(ref n) if !n.is_sign_positive() => GROUP_BY,
+        AstKind::Unary(UnaryOp::Minus(..)) => GROUP_BY,
+        AstKind::Binary(ref op, ..) => binary_precedence(op),
+        AstKind::GroupBy(..) => GROUP_BY,
+        AstKind::OrderBy(..) => 40,
+        AstKind::Ternary { .. } => 20,
+        AstKind::Function { .. } => POSTFIX,
+        AstKind::Lambda {
+            ref body,
+            thunk: true,
+            ..
+        } => precedence(body),
+        AstKind::Path(ref steps) => {
+            if steps
+                .iter()
+                .any(|step| matches!(step.kind, AstKind::Sort(..)))
+            {
+                40
+            } else if steps.len() == 1 {
+                precedence(&steps[0])
+            } else {
+                PATH
+            }
+        }
+        _ => PRIMARY,
+    }
+}
+
+// The precedence of the node as a whole, including its predicates and group by
+fn precedence(node: &Ast) -> u32 {
+    let mut precedence = base_precedence(node);
+    if has_filters(node) {
+        precedence = precedence.min(POSTFIX);
+    }
+    if node.group_by.is_some() {
+        precedence = precedence.min(GROUP_BY);
+    }
+    precedence
+}
+
+fn has_filters(node: &Ast) -> bool {
+    let is_path = matches!(node.kind, AstKind::Path(..));
+    (node.keep_array && !is_path) || node.predicates.is_some() || node.stages.is_some()
+}
+
+fn write_operand(node: &Ast, min_precedence: u32, out: &mut String) {
+    if precedence(node) < min_precedence {
+        out.push('(');
+        write_node(node, out);
+        out.push(')');
+    } else {
+        write_node(node, out);
+    }
+}
+
+fn write_node(node: &Ast, out: &mut String) {
+    if has_filters(node) {
+        write_operand_base(node, POSTFIX, out);
+        if node.keep_array && !matches!(node.kind, AstKind::Path(..)) {
+            out.push_str("[]");
+        }
+        for filter in node.predicates.iter().chain(node.stages.iter()).flatten() {
+            out.push('[');
+            match filter.kind {
+                AstKind::Filter(ref expr) => write_node(expr, out),
+                _ => write_node(filter, out),
+            }
+            out.push(']');
+        }
+    } else if node.group_by.is_some() {
+        write_operand_base(node, GROUP_BY, out);
+    } else {
+        write_base(node, out);
+    }
+
+    if let Some((_, ref object)) = node.group_by {
+        write_object(object, out);
+    }
+}
+
+fn write_operand_base(node: &Ast, min_precedence: u32, out: &mut String) {
+    if base_precedence(node) < min_precedence {
+        out.push('(');
+        write_base(node, out);
+        out.push(')');
+    } else {
+        write_base(node, out);
+    }
+}
+
+fn write_base(node: &Ast, out: &mut String) {
+    match node.kind {
+        AstKind::Empty => {}
+        AstKind::Null => out.push_str("null"),
+        AstKind::Bool(b) => out.push_str(if b { "true" } else { "false" }),
+        AstKind::String(ref s) => write_string(s, out),
+        AstKind::Number(ref n) => write_number(*n, out),
+        AstKind::Name(ref name) => write_name(name, out),
+        AstKind::Var(ref name) => {
+            out.push('$');
+            out.push_str(name);
+        }
+        AstKind::Wildcard => out.push('*'),
+        AstKind::Descendent => out.push_str("**"),
+        AstKind::Parent => out.push('%'),
+        AstKind::PartialArg => out.push('?'),
+        AstKind::Unary(UnaryOp::Minus(ref operand)) => {
+            out.push('-');
+            write_operand(operand, GROUP_BY + 1, out);
+        }
+        AstKind::Unary(UnaryOp::ArrayConstructor(ref items)) => {
+            out.push('[');
+            write_list(items, ", ", out);
+            out.push(']');
+        }
+        AstKind::Unary(UnaryOp::ObjectConstructor(ref object)) => write_object(object, out),
+        AstKind::Binary(BinaryOp::Range, ref lhs, ref rhs) => {
+            write_node(lhs, out);
+            out.push_str("..");
+            write_node(rhs, out);
+        }
+        AstKind::Binary(BinaryOp::Map, ref lhs, ref rhs) => {
+            write_operand(lhs, PATH, out);
+            out.push('.');
+            write_operand(rhs, PATH + 1, out);
+        }
+        AstKind::Binary(BinaryOp::Predicate, ref lhs, ref rhs) => {
+            write_operand(lhs, POSTFIX, out);
+            out.push('[');
+            write_node(rhs, out);
+            out.push(']');
+        }
+        AstKind::Binary(
+            ref op @ (BinaryOp::ContextBind | BinaryOp::PositionalBind),
+            ref lhs,
+            ref rhs,
+        ) => {
+            write_operand(lhs, POSTFIX, out);
+            out.push_str(&op.to_string());
+            write_node(rhs, out);
+        }
+        AstKind::Binary(BinaryOp::Bind, ref lhs, ref rhs) => {
+            write_node(lhs, out);
+            out.push_str(" := ");
+            write_operand(rhs, binary_precedence(&BinaryOp::Bind), out);
+        }
+        AstKind::Binary(ref op, ref lhs, ref rhs) => {
+            let precedence = binary_precedence(op);
+            write_operand(lhs, precedence, out);
+            out.push(' ');
+            out.push_str(&op.to_string());
+            out.push(' ');
+            write_operand(rhs, precedence + 1, out);
+        }
+        AstKind::GroupBy(ref lhs, ref object) => {
+            write_operand(lhs, GROUP_BY, out);
+            write_object(object, out);
+        }
+        AstKind::OrderBy(ref lhs, ref terms) => {
+            write_operand(lhs, 40, out);
+            write_sort_terms(terms, out);
+        }
+        AstKind::Sort(ref terms) => write_sort_terms(terms, out),
+        AstKind::Block(ref exprs) => {
+            out.push('(');
+            write_list(exprs, "; ", out);
+            out.push(')');
+        }
+        AstKind::Function {
+            ref proc, ref args, ..
+        } => {
+            write_operand(proc, POSTFIX, out);
+            out.push('(');
+            write_list(args, ", ", out);
+            out.push(')');
+        }
+        AstKind::Lambda {
+            ref body,
+            thunk: true,
+            ..
+        } => write_node(body, out),
+        AstKind::Lambda {
+            ref args,
+            ref body,
+            ref signature,
+            ..
+        } => {
+            out.push_str("function(");
+            write_list(args, ", ", out);
+            out.push(')');
+            if let Some(ref signature) = signature {
+                out.push('<');
+                write_signature(signature, out);
+                out.push('>');
+            }
+            out.push('{');
+            write_node(body, out);
+            out.push('}');
+        }
+        AstKind::Ternary {
+            ref cond,
+            ref truthy,
+            ref falsy,
+        } => {
+            write_operand(cond, 21, out);
+            out.push_str(" ? ");
+
+            // A nested conditional without an else would take this one's else as its own
+            if falsy.is_some() && matches!(truthy.kind, AstKind::Ternary { falsy: None, .. }) {
+                out.push('(');
+                write_node(truthy, out);
+                out.push(')');
+            } else {
+                write_node(truthy, out);
+            }
+
+            if let Some(ref falsy) = falsy {
+                out.push_str(" : ");
+                write_node(falsy, out);
+            }
+        }
+        AstKind::Transform {
+            ref pattern,
+            ref update,
+            ref delete,
+        } => {
+            out.push('|');
+            write_node(pattern, out);
+            out.push('|');
+            write_node(update, out);
+            if let Some(ref delete) = delete {
+                out.push_str(", ");
+                write_node(delete, out);
+            }
+            out.push('|');
+        }
+        AstKind::Path(ref steps) => {
+            for (index, step) in steps.iter().enumerate() {
+                if let AstKind::Sort(..) = step.kind {
+                    write_node(step, out);
+                    continue;
+                }
+                if index > 0 {
+                    out.push('.');
+                }
+                write_operand(step, PATH + 1, out);
+            }
+
+            // Keeping singleton arrays on an ordered path is recorded on the path itself
+            if node.keep_array && !steps.iter().any(|step| step.keep_array) {
+                out.push_str("[]");
+            }
+        }
+        AstKind::Filter(ref expr) => {
+            out.push('[');
+            write_node(expr, out);
+            out.push(']');
+        }
+    }
+}
+
+fn write_list(nodes: &[Ast], separator: &str, out: &mut String) {
+    for (index, node) in nodes.iter().enumerate() {
+        if index > 0 {
+            out.push_str(separator);
+        }
+        write_node(node, out);
+    }
+}
+
+fn write_object(object: &Object, out: &mut String) {
+    out.push('{');
+    for (index, (key, value)) in object.iter().enumerate() {
+        if index > 0 {
+            out.push_str(", ");
+        }
+        write_node(key, out);
+        out.push_str(": ");
+        write_node(value, out);
+    }
+    out.push('}');
+}
+
+fn write_sort_terms(terms: &SortTerms, out: &mut String) {
+    out.push_str("^(");
+    for (index, (term, descending)) in terms.iter().enumerate() {
+        if index > 0 {
+            out.push_str(", ");
+        }
+        if *descending {
+            out.push('>');
+        }
+        write_node(term, out);
+    }
+    out.push(')');
+}
+
+// Names that aren't plain identifiers, or that would be read as a keyword, need backticks
+fn write_name(name: &str, out: &mut String) {
+    let is_plain = name.starts_with(char::is_alphabetic)
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && !matches!(
+            name,
+            "and" | "or" | "in" | "true" | "false" | "null" | "function"
+        );
+
+    if is_plain {
+        out.push_str(name);
+    } else {
+        out.push('`');
+        out.push_str(name);
+        out.push('`');
+    }
+}
+
+// Large integers are written in exponent notation, as the tokenizer only reads integer literals
+// with as many digits as fit in its mantissa
+fn write_number(n: Number, out: &mut String) {
+    let value = f64::from(n);
+    if value.abs() >= 1e17 && value.abs() < 1e21 {
+        out.push_str(&format!("{:e}", value));
+    } else {
+        out.push_str(&n.to_string());
+    }
+}
+
+fn write_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_signature(args: &[Arg], out: &mut String) {
+    for arg in args {
+        write_arg_kind(&arg.kind, out);
+        if arg.flags.contains(Flags::ONE_OR_MORE) {
+            out.push('+');
+        }
+        if arg.flags.contains(Flags::OPTIONAL) {
+            out.push('?');
+        }
+        if arg.flags.contains(Flags::ACCEPT_CONTEXT) {
+            out.push('-');
+        }
+    }
+}
+
+fn write_arg_kind(kind: &ArgKind, out: &mut String) {
+    match kind {
+        ArgKind::Null => out.push('l'),
+        ArgKind::Bool => out.push('b'),
+        ArgKind::Number => out.push('n'),
+        ArgKind::String => out.push('s'),
+        ArgKind::Object => out.push('o'),
+        ArgKind::Array(None) => out.push('a'),
+        ArgKind::Array(Some(kind)) => {
+            out.push_str("a<");
+            write_arg_kind(kind, out);
+            out.push('>');
+        }
+        ArgKind::Function(..) => out.push('f'),
+        ArgKind::Or(kinds) => {
+            out.push('(');
+            for kind in kinds {
+                write_arg_kind(kind, out);
+            }
+            out.push(')');
+        }
+    }
+}
@@ -33,7 +33,6 @@ pub fn process_ast(node: Ast) -> Result<Ast> {
         }
         AstKind::Ternary { .. } => process_ternary(node)?,
         AstKind::Transform { .. } => process_transform(node)?,
-        AstKind::Parent => unimplemented!("Parent not yet implemented"),
         _ => node,
     };
 
@@ -178,7 +177,6 @@ fn process_path(char_index: usize, lhs: &mut Box<Ast>, rhs: &mut Box<Ast>) -> Re
         Ast::new(AstKind::Path(vec![left_step]), char_index)
     };
 
-    // TODO: If the lhs is a Parent (parser.js:997)
     // TODO: If the rhs is a Function (parser.js:1001)
 
     if let AstKind::Path(ref mut steps) = result.kind {
@@ -248,8 +246,6 @@ fn process_predicate(char_index: usize, lhs: &mut Box<Ast>, rhs: &mut Box<Ast>)
         char_index,
     );
 
-    // TODO: seekingParent (parser.js:1074)
-
     // Add the filter to the node. If it's a step in a path, it goes in stages, otherwise in predicated
     if in_path {
         match node.stages {
@@ -1,7 +1,11 @@
 use bumpalo::Bump;
-use std::collections::{hash_map, HashMap};
+use chrono::{DateTime, Utc};
+use indexmap::{map, IndexMap};
+use std::cell::Cell;
+use std::collections::HashMap;
 
 use jsonata_errors::{Error, Result};
+use jsonata_signatures::Flags;
 
 use super::ast::*;
 use super::frame::Frame;
@@ -11,11 +15,79 @@ use super::value::{ArrayFlags, Value};
 pub struct Evaluator<'a> {
     chain_ast: Ast,
     arena: &'a Bump,
+
+    /// When set, recoverable errors in a subexpression evaluate to undefined instead of aborting
+    /// the whole evaluation. See `is_recoverable` for the errors this applies to.
+    lenient_errors: bool,
+
+    /// The time the evaluation started, so that every call to `$now()` within one evaluation
+    /// returns the same timestamp.
+    timestamp: DateTime<Utc>,
+
+    /// The largest sequence the range operator (`..`) is allowed to allocate.
+    range_limit: usize,
+
+    /// The ancestry of the input currently being evaluated by a path step, which is what `%`
+    /// evaluates against.
+    ancestry: Cell<Option<&'a Ancestry<'a>>>,
+}
+
+/// The objects a value in a path was reached through, nearest first. Name and wildcard steps
+/// record their input as the parent of the values they produce, and a `%` step hands back the
+/// rest of the ancestry along with the parent.
+struct Ancestry<'a> {
+    parent: &'a Value<'a>,
+    ancestors: Option<&'a Ancestry<'a>>,
+}
+
+/// Recoverable errors are the type errors (`Txxxx` codes), which includes passing the wrong
+/// type of argument to a function and invoking something that isn't a function (for example
+/// an unknown function name). Everything else, such as numeric overflow or limits being
+/// exceeded, always aborts the evaluation.
+fn is_recoverable(error: &Error) -> bool {
+    error.code().starts_with('T')
 }
 
+/// The same ancestry for each member of `value`, or for `value` itself when it isn't an array,
+/// for when they were all reached from the same place.
+fn shared_ancestry<'a>(
+    value: &Value<'a>,
+    ancestry: Option<&'a Ancestry<'a>>,
+) -> Vec<Option<&'a Ancestry<'a>>> {
+    let len = if value.is_array() { value.len() } else { 1 };
+    vec![ancestry; len]
+}
+
+/// The default for the largest sequence the range operator is allowed to allocate, matching
+/// the limit in the reference implementation.
+pub const DEFAULT_RANGE_LIMIT: usize = 10_000_000;
+
 impl<'a> Evaluator<'a> {
     pub fn new(chain_ast: Ast, arena: &'a Bump) -> Self {
-        Evaluator { chain_ast, arena }
+        Evaluator {
+            chain_ast,
+            arena,
+            lenient_errors: false,
+            timestamp: Utc::now(),
+            range_limit: DEFAULT_RANGE_LIMIT,
+            ancestry: Cell::new(None),
+        }
+    }
+
+    pub fn set_lenient_errors(&mut self, lenient_errors: bool) {
+        self.lenient_errors = lenient_errors;
+    }
+
+    pub fn set_timestamp(&mut self, timestamp: DateTime<Utc>) {
+        self.timestamp = timestamp;
+    }
+
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    pub fn set_range_limit(&mut self, range_limit: usize) {
+        self.range_limit = range_limit;
     }
 
     fn fn_context<'e>(
@@ -41,12 +113,43 @@ impl<'a> Evaluator<'a> {
         input: &'a Value<'a>,
         frame: &Frame<'a>,
     ) -> Result<&'a Value<'a>> {
+        Ok(self.evaluate_with_ancestry(node, input, frame)?.0)
+    }
+
+    /// Evaluates a node along with the ancestry of each member of its result, so that a path
+    /// step can find the parents of what it evaluates to. Only paths, and blocks that end in
+    /// one, know the ancestry of their results.
+    fn evaluate_with_ancestry(
+        &self,
+        node: &Ast,
+        input: &'a Value<'a>,
+        frame: &Frame<'a>,
+    ) -> Result<(&'a Value<'a>, Option<Vec<Option<&'a Ancestry<'a>>>>)> {
+        match self.evaluate_node(node, input, frame) {
+            Err(error) if self.lenient_errors && is_recoverable(&error) => {
+                Ok((Value::undefined(), None))
+            }
+            result => result,
+        }
+    }
+
+    fn evaluate_node(
+        &self,
+        node: &Ast,
+        input: &'a Value<'a>,
+        frame: &Frame<'a>,
+    ) -> Result<(&'a Value<'a>, Option<Vec<Option<&'a Ancestry<'a>>>>)> {
+        let mut ancestry = None;
         let mut result = match node.kind {
             AstKind::Null => Value::null(self.arena),
             AstKind::Bool(b) => Value::bool(self.arena, b),
             AstKind::String(ref s) => Value::string(self.arena, String::from(s)),
             AstKind::Number(n) => Value::number(self.arena, n),
-            AstKind::Block(ref exprs) => self.evaluate_block(exprs, input, frame)?,
+            AstKind::Block(ref exprs) => {
+                let (result, block_ancestry) = self.evaluate_block(exprs, input, frame)?;
+                ancestry = block_ancestry;
+                result
+            }
             AstKind::Unary(ref op) => self.evaluate_unary_op(node, op, input, frame)?,
             AstKind::Binary(ref op, ref lhs, ref rhs) => {
                 self.evaluate_binary_op(node, op, lhs, rhs, input, frame)?
@@ -57,13 +160,22 @@ impl<'a> Evaluator<'a> {
                 ref truthy,
                 ref falsy,
             } => self.evaluate_ternary(cond, truthy, falsy.as_deref(), input, frame)?,
-            AstKind::Path(ref steps) => self.evaluate_path(node, steps, input, frame)?,
+            AstKind::Path(ref steps) => {
+                let (result, path_ancestry) = self.evaluate_path(node, steps, input, frame)?;
+                ancestry = Some(path_ancestry);
+                result
+            }
             AstKind::Name(ref name) => fn_lookup_internal(
                 self.fn_context("lookup", node.char_index, input, frame),
                 input,
                 name,
             ),
-            AstKind::Lambda { .. } => Value::lambda(self.arena, node, input, frame.clone()),
+            AstKind::Wildcard => self.evaluate_wildcard(input)?,
+            AstKind::Descendent => self.evaluate_descendents(input)?,
+            AstKind::Parent => self.evaluate_parent(),
+            AstKind::Lambda { .. } | AstKind::Transform { .. } => {
+                Value::lambda(self.arena, node, input, frame.clone())
+            }
             AstKind::Function {
                 ref proc,
                 ref args,
@@ -74,9 +186,12 @@ impl<'a> Evaluator<'a> {
         };
 
         if let Some(filters) = &node.predicates {
+            let mut filter_ancestry = ancestry.unwrap_or_else(|| shared_ancestry(result, None));
             for filter in filters {
-                result = self.evaluate_filter(filter, result, frame)?
+                (result, filter_ancestry) =
+                    self.evaluate_filter(filter, result, &filter_ancestry, frame)?
             }
+            ancestry = Some(filter_ancestry);
         }
 
         Ok(if result.has_flags(ArrayFlags::SEQUENCE) {
@@ -85,18 +200,22 @@ impl<'a> Evaluator<'a> {
                     .clone_array_with_flags(self.arena, result.get_flags() | ArrayFlags::SINGLETON)
             }
             if result.is_empty() {
-                Value::undefined()
+                (Value::undefined(), None)
             } else if result.len() == 1 {
                 if result.has_flags(ArrayFlags::SINGLETON) {
-                    result
+                    (result, ancestry)
                 } else {
-                    result.get_member(0)
+                    let member = result.get_member(0);
+                    let ancestry = ancestry.map(|ancestry| {
+                        shared_ancestry(member, ancestry.first().copied().flatten())
+                    });
+                    (member, ancestry)
                 }
             } else {
-                result
+                (result, ancestry)
             }
         } else {
-            result
+            (result, ancestry)
         })
     }
 
@@ -105,15 +224,15 @@ impl<'a> Evaluator<'a> {
         exprs: &[Ast],
         input: &'a Value<'a>,
         frame: &Frame<'a>,
-    ) -> Result<&'a Value<'a>> {
+    ) -> Result<(&'a Value<'a>, Option<Vec<Option<&'a Ancestry<'a>>>>)> {
         let frame = Frame::new_with_parent(frame);
         if exprs.is_empty() {
-            return Ok(Value::undefined());
+            return Ok((Value::undefined(), None));
         }
 
-        let mut result = Value::undefined();
+        let mut result = (Value::undefined(), None);
         for expr in exprs {
-            result = self.evaluate(expr, input, &frame)?;
+            result = self.evaluate_with_ancestry(expr, input, &frame)?;
         }
 
         Ok(result)
@@ -138,6 +257,87 @@ impl<'a> Evaluator<'a> {
         })
     }
 
+    fn evaluate_wildcard(&self, input: &'a Value<'a>) -> Result<&'a Value<'a>> {
+        // Nested arrays in the values are flattened all the way down into the result
+        fn flatten_into<'a>(result: &mut Value<'a>, value: &'a Value<'a>) {
+            if value.is_array() {
+                value
+                    .members()
+                    .for_each(|member| flatten_into(result, member));
+            } else {
+                result.push(value);
+            }
+        }
+
+        let result = Value::array(self.arena, ArrayFlags::SEQUENCE);
+
+        let input = if input.has_flags(ArrayFlags::WRAPPED) && !input.is_empty() {
+            input.get_member(0)
+        } else {
+            input
+        };
+
+        match input {
+            Value::Object(..) => input
+                .entries()
+                .for_each(|(_, value)| flatten_into(result, value)),
+            Value::Array(..) => input
+                .members()
+                .for_each(|member| flatten_into(result, member)),
+            _ => {}
+        }
+
+        Ok(result)
+    }
+
+    fn evaluate_descendents(&self, input: &'a Value<'a>) -> Result<&'a Value<'a>> {
+        // Every value is visited depth first, in key order for objects, including the input
+        // itself and any objects on the way down, but arrays are only walked through
+        fn recurse<'a>(result: &mut Value<'a>, value: &'a Value<'a>) {
+            if value.is_array() {
+                value.members().for_each(|member| recurse(result, member));
+            } else {
+                result.push(value);
+                if value.is_object() {
+                    value
+                        .entries()
+                        .for_each(|(_, value)| recurse(result, value));
+                }
+            }
+        }
+
+        if input.is_undefined() {
+            return Ok(input);
+        }
+
+        let result = Value::array(self.arena, ArrayFlags::SEQUENCE);
+        recurse(result, input);
+
+        if result.len() == 1 {
+            Ok(result.get_member(0))
+        } else {
+            Ok(result)
+        }
+    }
+
+    /// Evaluates `%`, the object the input was reached from by the enclosing path step. There is
+    /// no parent outside of a path, or above the first step of one.
+    fn evaluate_parent(&self) -> &'a Value<'a> {
+        match self.ancestry.get() {
+            Some(ancestry) => ancestry.parent,
+            None => Value::undefined(),
+        }
+    }
+
+    /// Evaluates `f` with `ancestry` as the ancestry of its input, restoring the previous
+    /// ancestry afterwards.
+    fn with_ancestry<T>(&self, ancestry: Option<&'a Ancestry<'a>>, f: impl FnOnce() -> T) -> T {
+        let previous = self.ancestry.replace(ancestry);
+        let result = f();
+        self.ancestry.set(previous);
+        result
+    }
+
     fn evaluate_unary_op(
         &self,
         node: &Ast,
@@ -181,15 +381,21 @@ impl<'a> Evaluator<'a> {
                 Ok(result)
             }
             UnaryOp::ObjectConstructor(ref object) => {
-                self.evaluate_group_expression(node.char_index, object, input, frame)
+                self.evaluate_group_expression(node.char_index, object, true, input, frame)
             }
         }
     }
 
+    /// Evaluates an object constructor, grouping the input by the keys. Two different pairs
+    /// evaluating to the same key is an error (`D1009`), except that in a plain constructor
+    /// (`literal_keys_last_wins`) a string literal key repeated by a later pair is overridden by
+    /// it, e.g. `{"a": 1, "a": 2}` is `{"a": 2}`. A group by, e.g. `Product{"a": 1, "a": 2}`,
+    /// always raises the error.
     fn evaluate_group_expression(
         &self,
         char_index: usize,
         object: &[(Ast, Ast)],
+        literal_keys_last_wins: bool,
         input: &'a Value<'a>,
         frame: &Frame<'a>,
     ) -> Result<&'a Value<'a>> {
@@ -198,7 +404,7 @@ impl<'a> Evaluator<'a> {
             pub index: usize,
         }
 
-        let mut groups: HashMap<String, Group> = HashMap::new();
+        let mut groups: IndexMap<String, Group> = IndexMap::new();
 
         let input = if input.is_array() && input.is_empty() {
             let input = Value::array_with_capacity(self.arena, 1, ArrayFlags::empty());
@@ -212,8 +418,19 @@ impl<'a> Evaluator<'a> {
             input
         };
 
+        let is_overridden = |index: usize| match object[index].0.kind {
+            AstKind::String(ref key) if literal_keys_last_wins => object[index + 1..]
+                .iter()
+                .any(|(later, _)| matches!(later.kind, AstKind::String(ref k) if k == key)),
+            _ => false,
+        };
+
         for item in input.members() {
             for (index, pair) in object.iter().enumerate() {
+                if is_overridden(index) {
+                    continue;
+                }
+
                 let key = self.evaluate(&pair.0, item, frame)?;
                 if !key.is_string() {
                     return Err(Error::T1003NonStringKey(char_index, key.dump()));
@@ -222,7 +439,7 @@ impl<'a> Evaluator<'a> {
                 let key = key.as_str();
 
                 match groups.entry(key.to_string()) {
-                    hash_map::Entry::Occupied(mut entry) => {
+                    map::Entry::Occupied(mut entry) => {
                         let group = entry.get_mut();
                         if group.index != index {
                             return Err(Error::D1009MultipleKeys(char_index, key.to_string()));
@@ -233,7 +450,7 @@ impl<'a> Evaluator<'a> {
                             item,
                         )?;
                     }
-                    hash_map::Entry::Vacant(entry) => {
+                    map::Entry::Vacant(entry) => {
                         entry.insert(Group { data: item, index });
                     }
                 };
@@ -242,8 +459,7 @@ impl<'a> Evaluator<'a> {
 
         let result = Value::object(self.arena);
 
-        for key in groups.keys() {
-            let group = groups.get(key).unwrap();
+        for (key, group) in groups.iter() {
             let value = self.evaluate(&object[group.index].1, group.data, frame)?;
             if !value.is_undefined() {
                 result.insert(key, value);
@@ -262,13 +478,15 @@ impl<'a> Evaluator<'a> {
         input: &'a Value<'a>,
         frame: &Frame<'a>,
     ) -> Result<&'a Value<'a>> {
+        // The parser rejects any bind without a variable on the left (S0212), so anything else
+        // reaching here is a parser bug rather than something to silently ignore.
         if *op == BinaryOp::Bind {
             if let AstKind::Var(ref name) = lhs_ast.kind {
                 let rhs = self.evaluate(rhs_ast, input, frame)?;
                 frame.bind(name, rhs);
                 return Ok(rhs);
             }
-            unreachable!()
+            unreachable!("Bind with a non-variable left side: {:?}", lhs_ast.kind)
         }
 
         // NOTE: rhs is not evaluated until absolutely necessary to support short circuiting
@@ -331,6 +549,12 @@ impl<'a> Evaluator<'a> {
                     return Ok(Value::undefined());
                 }
 
+                // JSONata doesn't order null against anything, but lenient consumers treat a
+                // missing value written out as null the same as one that's absent altogether
+                if self.lenient_errors && (lhs.is_null() || rhs.is_null()) {
+                    return Ok(Value::undefined());
+                }
+
                 if !((lhs.is_number() || lhs.is_string()) && (rhs.is_number() || rhs.is_string())) {
                     return Err(Error::T2010BinaryOpTypes(node.char_index, op.to_string()));
                 }
@@ -411,9 +635,12 @@ impl<'a> Evaluator<'a> {
                 }
 
                 let size = rhs - lhs + 1;
-                if size > 10_000_000 {
-                    // TODO: D2014
-                    unreachable!()
+                if size > self.range_limit {
+                    return Err(Error::D2014RangeOutOfBounds(
+                        node.char_index,
+                        self.range_limit,
+                        size,
+                    ));
                 }
 
                 let result = Value::array_with_capacity(self.arena, size, ArrayFlags::SEQUENCE);
@@ -432,6 +659,7 @@ impl<'a> Evaluator<'a> {
                         &fn_string(
                             self.fn_context("string", node.char_index, input, frame),
                             lhs,
+                            Value::undefined(),
                         )?
                         .as_str(),
                     );
@@ -441,6 +669,7 @@ impl<'a> Evaluator<'a> {
                         &fn_string(
                             self.fn_context("string", node.char_index, input, frame),
                             rhs,
+                            Value::undefined(),
                         )?
                         .as_str(),
                     );
@@ -459,21 +688,23 @@ impl<'a> Evaluator<'a> {
             )),
 
             BinaryOp::Apply => {
+                // A partial application on the right evaluates to a function like any other
+                // expression, which is then applied to lhs, so `s ~> $substring(?, 0, 3)` fills
+                // the placeholder with lhs rather than adding it as another argument
                 if let AstKind::Function {
                     ref proc,
                     ref args,
-                    is_partial,
+                    is_partial: false,
                     ..
                 } = rhs_ast.kind
                 {
                     // Function invocation with lhs as the first argument
-                    Ok(self.evaluate_function(input, proc, args, is_partial, frame, Some(lhs))?)
+                    Ok(self.evaluate_function(input, proc, args, false, frame, Some(lhs))?)
                 } else {
                     let rhs = self.evaluate(rhs_ast, input, frame)?;
 
                     if !rhs.is_function() {
-                        // TODO T2006
-                        unreachable!()
+                        return Err(Error::T2006RightSideNotFunction(rhs_ast.char_index));
                     }
 
                     if lhs.is_function() {
@@ -551,7 +782,7 @@ impl<'a> Evaluator<'a> {
         steps: &[Ast],
         input: &'a Value<'a>,
         frame: &Frame<'a>,
-    ) -> Result<&'a Value<'a>> {
+    ) -> Result<(&'a Value<'a>, Vec<Option<&'a Ancestry<'a>>>)> {
         let mut input = if input.is_array() && !matches!(steps[0].kind, AstKind::Var(..)) {
             input
         } else {
@@ -559,12 +790,14 @@ impl<'a> Evaluator<'a> {
         };
 
         let mut result = Value::undefined();
+        let mut ancestry = shared_ancestry(input, self.ancestry.get());
 
         for (index, step) in steps.iter().enumerate() {
-            result = if index == 0 && step.cons_array {
-                self.evaluate(step, input, frame)?
+            (result, ancestry) = if index == 0 && step.cons_array {
+                let result = self.evaluate(step, input, frame)?;
+                (result, shared_ancestry(result, None))
             } else {
-                self.evaluate_step(step, input, frame, index == steps.len() - 1)?
+                self.evaluate_step(step, input, &ancestry, frame, index == steps.len() - 1)?
             };
 
             if result.is_undefined() || (result.is_array() && result.is_empty()) {
@@ -584,98 +817,212 @@ impl<'a> Evaluator<'a> {
                     result,
                     flags | ArrayFlags::SEQUENCE | ArrayFlags::SINGLETON,
                 );
+                ancestry = vec![ancestry.first().copied().flatten()];
             }
             result = result.clone_array_with_flags(self.arena, flags | ArrayFlags::SINGLETON);
         }
 
         if let Some((char_index, ref object)) = node.group_by {
-            self.evaluate_group_expression(char_index, object, result, frame)
+            let result =
+                self.evaluate_group_expression(char_index, object, false, result, frame)?;
+            Ok((result, shared_ancestry(result, None)))
         } else {
-            Ok(result)
+            Ok((result, ancestry))
         }
     }
 
+    /// Evaluates a step of a path against each member of its input, given the ancestry of each
+    /// member. Returns the resulting sequence along with the ancestry of each of its members.
     fn evaluate_step(
         &self,
         step: &Ast,
         input: &'a Value<'a>,
+        ancestry: &[Option<&'a Ancestry<'a>>],
         frame: &Frame<'a>,
         last_step: bool,
-    ) -> Result<&'a Value<'a>> {
+    ) -> Result<(&'a Value<'a>, Vec<Option<&'a Ancestry<'a>>>)> {
         if let AstKind::Sort(ref sorts) = step.kind {
-            let mut result = self.evaluate_sorts(sorts, input, frame)?;
-            if let Some(ref stages) = step.stages {
-                result = self.evaluate_stages(stages, result, frame)?;
-            }
-            return Ok(result);
+            let (result, ancestry) =
+                self.evaluate_sorts(step.char_index, sorts, input, ancestry, frame)?;
+            return match step.stages {
+                Some(ref stages) => self.evaluate_stages(stages, result, ancestry, frame),
+                None => Ok((result, ancestry)),
+            };
         }
 
-        let result = Value::array(self.arena, ArrayFlags::SEQUENCE);
-
-        for item in input.members() {
-            let mut item_result = self.evaluate(step, item, frame)?;
+        let result = Value::array_with_capacity(self.arena, input.len(), ArrayFlags::SEQUENCE);
+        let mut result_ancestry = Vec::with_capacity(input.len());
+
+        for (index, item) in input.members().enumerate() {
+            let item_ancestry = ancestry.get(index).copied().flatten();
+            let (mut item_result, ancestry) = self.with_ancestry(item_ancestry, || {
+                self.evaluate_with_ancestry(step, item, frame)
+            })?;
+
+            // Navigating into the item finds values that it is the parent of, and `%` goes back
+            // up to the ancestry of the parent. Anything else a step evaluates to, such as a
+            // constructed object or a variable, has no parent unless it came from a path.
+            let mut ancestry = match step.kind {
+                AstKind::Name(..) | AstKind::Wildcard => {
+                    let ancestry = self.arena.alloc(Ancestry {
+                        parent: item,
+                        ancestors: item_ancestry,
+                    });
+                    shared_ancestry(item_result, Some(ancestry))
+                }
+                AstKind::Parent => {
+                    shared_ancestry(item_result, item_ancestry.and_then(|a| a.ancestors))
+                }
+                _ => ancestry.unwrap_or_else(|| shared_ancestry(item_result, None)),
+            };
 
             if let Some(ref stages) = step.stages {
                 for stage in stages {
-                    item_result = self.evaluate_filter(stage, item_result, frame)?;
+                    (item_result, ancestry) =
+                        self.evaluate_filter(stage, item_result, &ancestry, frame)?;
                 }
             }
 
             if !item_result.is_undefined() {
                 result.push(item_result);
+                result_ancestry.push(ancestry);
             }
         }
 
-        Ok(
-            if last_step
-                && result.len() == 1
-                && result.get_member(0).is_array()
-                && !result.get_member(0).has_flags(ArrayFlags::SEQUENCE)
-            {
-                result.get_member(0)
-            } else {
-                let result_sequence = Value::array(self.arena, ArrayFlags::SEQUENCE);
+        if last_step
+            && result.len() == 1
+            && result.get_member(0).is_array()
+            && !result.get_member(0).has_flags(ArrayFlags::SEQUENCE)
+        {
+            return Ok((result.get_member(0), result_ancestry.swap_remove(0)));
+        }
 
-                for result_item in result.members() {
-                    if !result_item.is_array() || result_item.has_flags(ArrayFlags::CONS) {
-                        result_sequence.push(result_item);
-                    } else {
-                        for item in result_item.members() {
-                            result_sequence.push(item);
-                        }
-                    }
+        // Array results are flattened into the sequence, unless they came from an array
+        // constructor. When there's nothing to flatten the results are already the sequence.
+        let flatten = |item: &Value| item.is_array() && !item.has_flags(ArrayFlags::CONS);
+        if !result.members().any(|item| flatten(item)) {
+            let ancestry = result_ancestry
+                .iter()
+                .map(|ancestry| ancestry.first().copied().flatten())
+                .collect();
+            return Ok((result, ancestry));
+        }
+
+        let result_sequence =
+            Value::array_with_capacity(self.arena, result.len(), ArrayFlags::SEQUENCE);
+        let mut sequence_ancestry = Vec::with_capacity(result.len());
+
+        for (result_item, ancestry) in result.members().zip(&result_ancestry) {
+            if flatten(result_item) {
+                for (index, item) in result_item.members().enumerate() {
+                    result_sequence.push(item);
+                    sequence_ancestry.push(ancestry.get(index).copied().flatten());
                 }
-                result_sequence
-            },
-        )
+            } else {
+                result_sequence.push(result_item);
+                sequence_ancestry.push(ancestry.first().copied().flatten());
+            }
+        }
+
+        Ok((result_sequence, sequence_ancestry))
     }
 
+    /// Sorts the input sequence by each of the sort terms in turn, where later terms only
+    /// order members that the earlier terms found equal. The sort is stable, and members with
+    /// an undefined sort key are placed last whether the term is ascending or descending.
     fn evaluate_sorts(
         &self,
-        _sorts: &[(Ast, bool)],
-        _inputt: &'a Value<'a>,
-        _frame: &Frame<'a>,
-    ) -> Result<&'a Value<'a>> {
-        unimplemented!("Sorts not yet implemented")
+        char_index: usize,
+        sorts: &[(Ast, bool)],
+        input: &'a Value<'a>,
+        ancestry: &[Option<&'a Ancestry<'a>>],
+        frame: &Frame<'a>,
+    ) -> Result<(&'a Value<'a>, Vec<Option<&'a Ancestry<'a>>>)> {
+        let input = Value::wrap_in_array_if_needed(self.arena, input, ArrayFlags::SEQUENCE);
+
+        // Members are sorted along with their ancestry, so that the sort terms and later steps
+        // can still refer to their parents
+        let members = input
+            .members()
+            .enumerate()
+            .map(|(index, member)| (*member, ancestry.get(index).copied().flatten()))
+            .collect::<Vec<_>>();
+
+        let sorted = merge_sort(members, &mut |(a, a_ancestry), (b, b_ancestry)| {
+            for (term, descending) in sorts {
+                let a = self.with_ancestry(a_ancestry, || self.evaluate(term, a, frame))?;
+                let b = self.with_ancestry(b_ancestry, || self.evaluate(term, b, frame))?;
+
+                match (a.is_undefined(), b.is_undefined()) {
+                    (true, true) => continue,
+                    (true, false) => return Ok(true),
+                    (false, true) => return Ok(false),
+                    _ => {}
+                }
+
+                if !(a.is_number() || a.is_string()) || !(b.is_number() || b.is_string()) {
+                    return Err(Error::T2008SortNotNumberOrString(char_index));
+                }
+
+                let ordering = if a.is_number() && b.is_number() {
+                    a.as_f64().partial_cmp(&b.as_f64())
+                } else if a.is_string() && b.is_string() {
+                    Some(a.as_str().cmp(&b.as_str()))
+                } else {
+                    return Err(Error::T2007SortMismatch(char_index, a.dump(), b.dump()));
+                };
+
+                match ordering {
+                    Some(std::cmp::Ordering::Greater) => return Ok(!descending),
+                    Some(std::cmp::Ordering::Less) => return Ok(*descending),
+                    _ => continue,
+                }
+            }
+
+            Ok(false)
+        })?;
+
+        let result = Value::array_with_capacity(self.arena, sorted.len(), ArrayFlags::SEQUENCE);
+        let ancestry = sorted
+            .into_iter()
+            .map(|(member, ancestry)| {
+                result.push(member);
+                ancestry
+            })
+            .collect();
+
+        Ok((result, ancestry))
     }
 
+    /// Applies the predicates attached to a step to its whole result, in order, e.g. the `[0]`
+    /// in `Account.Order^(Price)[0]` is applied after sorting.
     fn evaluate_stages(
         &self,
-        _stages: &[Ast],
-        _input: &'a Value<'a>,
-        _frame: &Frame<'a>,
-    ) -> Result<&'a Value<'a>> {
-        unimplemented!("Stages not yet implemented")
+        stages: &[Ast],
+        input: &'a Value<'a>,
+        ancestry: Vec<Option<&'a Ancestry<'a>>>,
+        frame: &Frame<'a>,
+    ) -> Result<(&'a Value<'a>, Vec<Option<&'a Ancestry<'a>>>)> {
+        let mut result = (input, ancestry);
+        for stage in stages {
+            result = self.evaluate_filter(stage, result.0, &result.1, frame)?;
+        }
+        Ok(result)
     }
 
+    /// Filters the members of the input, given the ancestry of each member. Returns the
+    /// members that were kept along with their ancestry.
     fn evaluate_filter(
         &self,
         node: &Ast,
         input: &'a Value<'a>,
+        ancestry: &[Option<&'a Ancestry<'a>>],
         frame: &Frame<'a>,
-    ) -> Result<&'a Value<'a>> {
+    ) -> Result<(&'a Value<'a>, Vec<Option<&'a Ancestry<'a>>>)> {
         let result = Value::array(self.arena, ArrayFlags::SEQUENCE);
+        let mut result_ancestry = Vec::new();
         let input = Value::wrap_in_array_if_needed(self.arena, input, ArrayFlags::empty());
+        let ancestry_of = |index: usize| ancestry.get(index).copied().flatten();
 
         let get_index = |n: f64| {
             let mut index = n.floor() as isize;
@@ -698,15 +1045,17 @@ impl<'a> Evaluator<'a> {
                     let item = input.get_member(index as usize);
                     if !item.is_undefined() {
                         if item.is_array() {
-                            return Ok(item);
+                            return Ok((item, shared_ancestry(item, ancestry_of(index))));
                         } else {
                             result.push(item);
+                            result_ancestry.push(ancestry_of(index));
                         }
                     }
                 }
                 _ => {
                     for (i, item) in input.members().enumerate() {
-                        let mut index = self.evaluate(filter, item, frame)?;
+                        let mut index = self
+                            .with_ancestry(ancestry_of(i), || self.evaluate(filter, item, frame))?;
                         if index.is_number() && !index.is_nan() {
                             index = Value::wrap_in_array(self.arena, index, ArrayFlags::empty());
                         }
@@ -716,10 +1065,12 @@ impl<'a> Evaluator<'a> {
                                 let index = get_index(v.as_f64());
                                 if index == i {
                                     result.push(item);
+                                    result_ancestry.push(ancestry_of(i));
                                 }
                             });
                         } else if index.is_truthy() {
                             result.push(item);
+                            result_ancestry.push(ancestry_of(i));
                         }
                     }
                 }
@@ -727,7 +1078,7 @@ impl<'a> Evaluator<'a> {
             _ => unimplemented!("Filters other than numbers are not yet supported"),
         };
 
-        Ok(result)
+        Ok((result, result_ancestry))
     }
 
     pub fn evaluate_function(
@@ -735,7 +1086,7 @@ impl<'a> Evaluator<'a> {
         input: &'a Value<'a>,
         proc: &Ast,
         args: &[Ast],
-        _is_partial: bool,
+        is_partial: bool,
         frame: &Frame<'a>,
         context: Option<&'a Value<'a>>,
     ) -> Result<&'a Value<'a>> {
@@ -746,15 +1097,26 @@ impl<'a> Evaluator<'a> {
             if let AstKind::Path(ref steps) = proc.kind {
                 if let AstKind::Name(ref name) = steps[0].kind {
                     if frame.lookup(name).is_some() {
-                        return Err(Error::T1005InvokedNonFunctionSuggest(
-                            proc.char_index,
-                            name.clone(),
-                        ));
+                        return Err(if is_partial {
+                            Error::T1007InvokedNonFunctionPartialSuggest(
+                                proc.char_index,
+                                name.clone(),
+                            )
+                        } else {
+                            Error::T1005InvokedNonFunctionSuggest(proc.char_index, name.clone())
+                        });
                     }
                 }
             }
         }
 
+        if is_partial {
+            if !evaluated_proc.is_function() {
+                return Err(Error::T1008InvokedNonFunctionPartial(proc.char_index));
+            }
+            return self.partially_apply(proc.char_index, input, evaluated_proc, args, frame);
+        }
+
         let evaluated_args =
             Value::array_with_capacity(self.arena, args.len(), ArrayFlags::empty());
 
@@ -767,13 +1129,170 @@ impl<'a> Evaluator<'a> {
             evaluated_args.push(arg);
         }
 
-        let mut result = self.apply_function(
+        self.apply_function(
             proc.char_index,
             input,
             evaluated_proc,
             evaluated_args,
             frame,
-        )?;
+        )
+    }
+
+    /// Applies a transform, `|pattern|update, delete|`, which evaluates to a function. The result
+    /// is a copy of the input where each object matched by the pattern has the update object
+    /// merged into it, and then the keys named by the delete clause removed. The update and
+    /// delete clauses are evaluated against the matches in the original input.
+    fn apply_transform(
+        &self,
+        char_index: usize,
+        pattern: &Ast,
+        update: &Ast,
+        delete: Option<&Ast>,
+        input: &'a Value<'a>,
+        frame: &Frame<'a>,
+    ) -> Result<&'a Value<'a>> {
+        if input.is_undefined() {
+            return Ok(input);
+        }
+
+        let matches = self.evaluate(pattern, input, frame)?;
+        let matches = if matches.is_undefined() {
+            Value::array(self.arena, ArrayFlags::empty())
+        } else {
+            Value::wrap_in_array_if_needed(self.arena, matches, ArrayFlags::empty())
+        };
+
+        let mut changes = HashMap::new();
+
+        for matched in matches.members() {
+            let update = self.evaluate(update, matched, frame)?;
+            if !update.is_undefined() && !update.is_object() {
+                return Err(Error::T2011UpdateNotObject(char_index, update.dump()));
+            }
+
+            let mut deletions = Vec::new();
+            if let Some(delete) = delete {
+                let delete = self.evaluate(delete, matched, frame)?;
+                if !delete.is_undefined() {
+                    let keys =
+                        Value::wrap_in_array_if_needed(self.arena, delete, ArrayFlags::empty());
+                    if !keys.members().all(|key| key.is_string()) {
+                        return Err(Error::T2012DeleteNotStrings(char_index, delete.dump()));
+                    }
+                    deletions.extend(keys.members().map(|key| key.as_str().to_string()));
+                }
+            }
+
+            changes.insert(*matched as *const Value<'a>, (update, deletions));
+        }
+
+        // Copy the input, making the changes to each matched object on the way
+        fn copy<'a>(
+            arena: &'a Bump,
+            value: &'a Value<'a>,
+            changes: &HashMap<*const Value<'a>, (&'a Value<'a>, Vec<String>)>,
+        ) -> &'a Value<'a> {
+            match value {
+                Value::Array(..) => {
+                    let result = Value::array_with_capacity(arena, value.len(), value.get_flags());
+                    for member in value.members() {
+                        result.push(copy(arena, member, changes));
+                    }
+                    result
+                }
+                Value::Object(ref map) => {
+                    let mut result = IndexMap::with_capacity(map.len());
+                    for (key, member) in map.iter() {
+                        result.insert(key.clone(), copy(arena, member, changes));
+                    }
+                    if let Some((update, deletions)) = changes.get(&(value as *const Value<'a>)) {
+                        if update.is_object() {
+                            for (key, member) in update.entries() {
+                                result.insert(key.clone(), *member);
+                            }
+                        }
+                        for key in deletions {
+                            result.shift_remove(key);
+                        }
+                    }
+                    let object = Value::object_with_capacity(arena, result.len());
+                    for (key, member) in result {
+                        object.insert(&key, member);
+                    }
+                    object
+                }
+                _ => value,
+            }
+        }
+
+        Ok(copy(self.arena, input, &changes))
+    }
+
+    /// Partially applies a function, which evaluates to a lambda that takes the placeholder (`?`)
+    /// arguments and calls the function with them in place, alongside the arguments that were
+    /// supplied up front. The lambda's body is generated: the function and the supplied
+    /// arguments are bound in a frame of their own, under names that only the body refers to.
+    fn partially_apply(
+        &self,
+        char_index: usize,
+        input: &'a Value<'a>,
+        evaluated_proc: &'a Value<'a>,
+        args: &[Ast],
+        frame: &Frame<'a>,
+    ) -> Result<&'a Value<'a>> {
+        let partial_frame = Frame::new_with_parent(frame);
+        partial_frame.bind("proc", evaluated_proc);
+
+        let mut params = Vec::new();
+        let mut call_args = Vec::with_capacity(args.len());
+
+        for arg in args {
+            let name = call_args.len().to_string();
+            if let AstKind::PartialArg = arg.kind {
+                params.push(Ast::new(AstKind::Var(name.clone()), arg.char_index));
+            } else {
+                partial_frame.bind(&name, self.evaluate(arg, input, frame)?);
+            }
+            call_args.push(Ast::new(AstKind::Var(name), arg.char_index));
+        }
+
+        let body = Ast::new(
+            AstKind::Function {
+                name: String::new(),
+                proc: Box::new(Ast::new(AstKind::Var("proc".to_string()), char_index)),
+                args: call_args,
+                is_partial: false,
+            },
+            char_index,
+        );
+
+        let lambda = Ast::new(
+            AstKind::Lambda {
+                name: String::new(),
+                args: params,
+                body: Box::new(body),
+                signature: None,
+                thunk: false,
+            },
+            char_index,
+        );
+
+        Ok(Value::lambda(self.arena, &lambda, input, partial_frame))
+    }
+
+    /// Applies a function to its evaluated arguments. Tail calls in lambda bodies evaluate to
+    /// thunks, which are run to completion here so that every caller, including native functions
+    /// invoking callbacks, gets the final result.
+    pub fn apply_function(
+        &self,
+        char_index: usize,
+        input: &'a Value<'a>,
+        evaluated_proc: &'a Value<'a>,
+        evaluated_args: &'a Value<'a>,
+        frame: &Frame<'a>,
+    ) -> Result<&'a Value<'a>> {
+        let mut result =
+            self.apply_procedure(char_index, input, evaluated_proc, evaluated_args, frame)?;
 
         // Trampoline loop for tail-call optimization
         // TODO: This loop needs help
@@ -804,7 +1323,7 @@ impl<'a> Evaluator<'a> {
                     }
 
                     result =
-                        self.apply_function(proc.char_index, input, next, evaluated_args, frame)?;
+                        self.apply_procedure(proc.char_index, input, next, evaluated_args, frame)?;
                 } else {
                     unreachable!()
                 }
@@ -816,7 +1335,7 @@ impl<'a> Evaluator<'a> {
         Ok(result)
     }
 
-    pub fn apply_function(
+    fn apply_procedure(
         &self,
         char_index: usize,
         input: &'a Value<'a>,
@@ -831,6 +1350,22 @@ impl<'a> Evaluator<'a> {
                 input,
                 ..
             } => {
+                if let AstKind::Transform {
+                    ref pattern,
+                    ref update,
+                    ref delete,
+                } = ast.kind
+                {
+                    return self.apply_transform(
+                        ast.char_index,
+                        pattern,
+                        update,
+                        delete.as_deref(),
+                        evaluated_args.get_member(0),
+                        frame,
+                    );
+                }
+
                 if let AstKind::Lambda {
                     ref body, ref args, ..
                 } = ast.kind
@@ -838,7 +1373,8 @@ impl<'a> Evaluator<'a> {
                     // Create a new frame for use in the lambda, so it can have locals
                     let frame = Frame::new_with_parent(frame);
 
-                    // Bind the arguments to their respective names
+                    // Bind the arguments to their respective names. Arguments beyond the declared
+                    // parameters are ignored, and parameters without an argument are undefined
                     for (index, arg) in args.iter().enumerate() {
                         if let AstKind::Var(ref name) = arg.kind {
                             frame.bind(name, evaluated_args.get_member(index));
@@ -847,16 +1383,18 @@ impl<'a> Evaluator<'a> {
                         }
                     }
 
-                    // Evaluate the lambda!
+                    // Evaluate the lambda! The body sees the input from where the lambda was
+                    // defined, the same as JSONata, so `$` and relative paths inside a callback
+                    // don't refer to the value it was called with; that's only in its arguments
                     self.evaluate(body, input, &frame)
                 } else {
                     unreachable!()
                 }
             }
-            Value::NativeFn0(ref name, ref func) => {
+            Value::NativeFn0(ref name, _, ref func) => {
                 func(self.fn_context(name, char_index, input, frame))
             }
-            Value::NativeFn1(ref name, ref func) => {
+            Value::NativeFn1(ref name, _, ref func) => {
                 let context = self.fn_context(name, char_index, input, frame);
                 if evaluated_args.len() > 1 {
                     Err(Error::T0410ArgumentNotValid(
@@ -865,13 +1403,18 @@ impl<'a> Evaluator<'a> {
                         context.name.to_string(),
                     ))
                 } else if evaluated_args.is_empty() {
-                    // Some functions take the input as the first argument if one was not provided
-                    func(context, input)
+                    // Some functions take the input as the first argument if one was not provided,
+                    // in which case a wrapped top-level array is passed the same way `$` sees it
+                    if input.has_flags(ArrayFlags::WRAPPED) {
+                        func(context, input.get_member(0))
+                    } else {
+                        func(context, input)
+                    }
                 } else {
                     func(context, evaluated_args.get_member(0))
                 }
             }
-            Value::NativeFn2(ref name, ref func) => {
+            Value::NativeFn2(ref name, signature, ref func) => {
                 let context = self.fn_context(name, char_index, input, frame);
                 if evaluated_args.len() > 2 {
                     Err(Error::T0410ArgumentNotValid(
@@ -879,6 +1422,18 @@ impl<'a> Evaluator<'a> {
                         3,
                         context.name.to_string(),
                     ))
+                } else if evaluated_args.is_empty()
+                    && signature
+                        .first()
+                        .is_some_and(|arg| arg.flags.contains(Flags::ACCEPT_CONTEXT))
+                {
+                    // The same as for one argument functions, but only where the signature takes
+                    // the context in place of the first argument, e.g. `$string()` casts the input
+                    if input.has_flags(ArrayFlags::WRAPPED) {
+                        func(context, input.get_member(0), Value::undefined())
+                    } else {
+                        func(context, input, Value::undefined())
+                    }
                 } else {
                     func(
                         context,
@@ -887,7 +1442,7 @@ impl<'a> Evaluator<'a> {
                     )
                 }
             }
-            Value::NativeFn3(ref name, ref func) => {
+            Value::NativeFn3(ref name, _, ref func) => {
                 let context = self.fn_context(name, char_index, input, frame);
                 if evaluated_args.len() > 3 {
                     Err(Error::T0410ArgumentNotValid(
@@ -904,6 +1459,24 @@ impl<'a> Evaluator<'a> {
                     )
                 }
             }
+            Value::NativeFn4(ref name, _, ref func) => {
+                let context = self.fn_context(name, char_index, input, frame);
+                if evaluated_args.len() > 4 {
+                    Err(Error::T0410ArgumentNotValid(
+                        context.char_index,
+                        5,
+                        context.name.to_string(),
+                    ))
+                } else {
+                    func(
+                        context,
+                        evaluated_args.get_member(0),
+                        evaluated_args.get_member(1),
+                        evaluated_args.get_member(2),
+                        evaluated_args.get_member(3),
+                    )
+                }
+            }
             _ => Err(Error::T1006InvokedNonFunction(char_index)),
         }
     }
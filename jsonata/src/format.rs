@@ -0,0 +1,934 @@
+//! Formatting of numbers according to an XPath style picture string, as used by
+//! `$formatInteger` and `$formatNumber`.
+
+use jsonata_errors::{Error, Result};
+
+const FEW: [&str; 20] = [
+    "Zero",
+    "One",
+    "Two",
+    "Three",
+    "Four",
+    "Five",
+    "Six",
+    "Seven",
+    "Eight",
+    "Nine",
+    "Ten",
+    "Eleven",
+    "Twelve",
+    "Thirteen",
+    "Fourteen",
+    "Fifteen",
+    "Sixteen",
+    "Seventeen",
+    "Eighteen",
+    "Nineteen",
+];
+
+const ORDINALS: [&str; 20] = [
+    "Zeroth",
+    "First",
+    "Second",
+    "Third",
+    "Fourth",
+    "Fifth",
+    "Sixth",
+    "Seventh",
+    "Eighth",
+    "Ninth",
+    "Tenth",
+    "Eleventh",
+    "Twelfth",
+    "Thirteenth",
+    "Fourteenth",
+    "Fifteenth",
+    "Sixteenth",
+    "Seventeenth",
+    "Eighteenth",
+    "Nineteenth",
+];
+
+const DECADES: [&str; 8] = [
+    "Twenty", "Thirty", "Forty", "Fifty", "Sixty", "Seventy", "Eighty", "Ninety",
+];
+
+const MAGNITUDES: [&str; 4] = ["Thousand", "Million", "Billion", "Trillion"];
+
+const ROMAN_NUMERALS: [(u64, &str); 13] = [
+    (1000, "m"),
+    (900, "cm"),
+    (500, "d"),
+    (400, "cd"),
+    (100, "c"),
+    (90, "xc"),
+    (50, "l"),
+    (40, "xl"),
+    (10, "x"),
+    (9, "ix"),
+    (5, "v"),
+    (4, "iv"),
+    (1, "i"),
+];
+
+// The zero digit of each group of Unicode decimal digits that can be used in a picture
+const DECIMAL_GROUPS: [u32; 37] = [
+    0x30, 0x0660, 0x06F0, 0x07C0, 0x0966, 0x09E6, 0x0A66, 0x0AE6, 0x0B66, 0x0BE6, 0x0C66, 0x0CE6,
+    0x0D66, 0x0DE6, 0x0E50, 0x0ED0, 0x0F20, 0x1040, 0x1090, 0x17E0, 0x1810, 0x1946, 0x19D0, 0x1A80,
+    0x1A90, 0x1B50, 0x1BB0, 0x1C40, 0x1C50, 0xA620, 0xA8D0, 0xA900, 0xA9D0, 0xA9F0, 0xAA50, 0xABF0,
+    0xFF10,
+];
+
+#[derive(Debug, PartialEq)]
+enum Case {
+    Lower,
+    Upper,
+    Title,
+}
+
+#[derive(Debug)]
+struct GroupingSeparator {
+    // Number of digits to the right of the separator
+    position: usize,
+    character: char,
+}
+
+#[derive(Debug)]
+enum Grouping {
+    // The same separator repeated every `position` digits
+    Regular(GroupingSeparator),
+    Irregular(Vec<GroupingSeparator>),
+}
+
+#[derive(Debug)]
+enum Primary {
+    Decimal {
+        zero_code: u32,
+        mandatory_digits: usize,
+        grouping: Grouping,
+    },
+    Letters,
+    Roman,
+    Words,
+}
+
+#[derive(Debug)]
+struct IntegerFormat {
+    primary: Primary,
+    case: Case,
+    ordinal: bool,
+}
+
+/// Formats an integer using a picture string, e.g. `#,##0` for grouped digits, `0;o` for
+/// ordinals (`1st`), `I` or `i` for roman numerals, `w`, `W` or `Ww` for words and `A` or `a`
+/// for letters. Non-integers are rounded down first.
+///
+/// Negative numbers are formatted as their absolute value with a leading `-` in every format.
+/// Zero has no roman numeral or letter sequence, so those formats give an empty string for it,
+/// while in words it's `zero`.
+pub fn format_integer(char_index: usize, value: f64, picture: &str) -> Result<String> {
+    let format = analyse_integer_picture(char_index, picture)?;
+
+    let value = value.floor();
+    let negative = value < 0.0;
+    let value = value.abs();
+
+    let mut formatted = match format.primary {
+        Primary::Letters => {
+            let a = if format.case == Case::Upper {
+                b'A'
+            } else {
+                b'a'
+            };
+            to_letters(value, a)
+        }
+        Primary::Roman => {
+            let roman = to_roman(value);
+            if format.case == Case::Upper {
+                roman.to_uppercase()
+            } else {
+                roman
+            }
+        }
+        Primary::Words => {
+            let words = to_words(value, false, format.ordinal);
+            match format.case {
+                Case::Lower => words.to_lowercase(),
+                Case::Upper => words.to_uppercase(),
+                Case::Title => words,
+            }
+        }
+        Primary::Decimal {
+            zero_code,
+            mandatory_digits,
+            ref grouping,
+        } => {
+            let digits = format!("{:.0}", value);
+            let mut chars: Vec<char> =
+                std::iter::repeat_n('0', mandatory_digits.saturating_sub(digits.len()))
+                    .chain(digits.chars())
+                    .map(|c| char::from_u32(c as u32 - 0x30 + zero_code).unwrap_or(c))
+                    .collect();
+
+            match grouping {
+                Grouping::Regular(separator) => {
+                    let count = (chars.len() - 1) / separator.position;
+                    for index in (1..=count).rev() {
+                        let position = chars.len() - index * separator.position;
+                        chars.insert(position, separator.character);
+                    }
+                }
+                Grouping::Irregular(separators) => {
+                    for separator in separators.iter().rev() {
+                        if separator.position < chars.len() {
+                            let position = chars.len() - separator.position;
+                            chars.insert(position, separator.character);
+                        }
+                    }
+                }
+            }
+
+            let mut formatted: String = chars.into_iter().collect();
+
+            if format.ordinal {
+                let mut last_digits = formatted.chars().rev();
+                let last = last_digits.next();
+                let tens = last_digits.next();
+                let suffix = match (tens, last) {
+                    (Some('1'), _) => "th",
+                    (_, Some('1')) => "st",
+                    (_, Some('2')) => "nd",
+                    (_, Some('3')) => "rd",
+                    _ => "th",
+                };
+                formatted.push_str(suffix);
+            }
+
+            formatted
+        }
+    };
+
+    if negative {
+        formatted.insert(0, '-');
+    }
+
+    Ok(formatted)
+}
+
+fn analyse_integer_picture(char_index: usize, picture: &str) -> Result<IntegerFormat> {
+    let (primary, modifier) = match picture.rfind(';') {
+        Some(index) => (&picture[..index], Some(&picture[index + 1..])),
+        None => (picture, None),
+    };
+
+    let ordinal = modifier.is_some_and(|modifier| modifier.starts_with('o'));
+
+    let (primary, case) = match primary {
+        "A" => (Primary::Letters, Case::Upper),
+        "a" => (Primary::Letters, Case::Lower),
+        "I" => (Primary::Roman, Case::Upper),
+        "i" => (Primary::Roman, Case::Lower),
+        "W" => (Primary::Words, Case::Upper),
+        "Ww" => (Primary::Words, Case::Title),
+        "w" => (Primary::Words, Case::Lower),
+        _ => (analyse_decimal_picture(char_index, primary)?, Case::Lower),
+    };
+
+    Ok(IntegerFormat {
+        primary,
+        case,
+        ordinal,
+    })
+}
+
+// A decimal digit pattern is made up of digits from any one Unicode decimal digit group, `#`
+// for optional digits, and anything else as grouping separators
+fn analyse_decimal_picture(char_index: usize, picture: &str) -> Result<Primary> {
+    let mut zero_code = None;
+    let mut mandatory_digits = 0;
+    let mut separators = Vec::new();
+    let mut position = 0;
+
+    // Separator positions are counted in digits from the right
+    for c in picture.chars().rev() {
+        let code = c as u32;
+        let group = DECIMAL_GROUPS
+            .iter()
+            .find(|&&group| (group..=group + 9).contains(&code));
+
+        match group {
+            Some(&group) => {
+                mandatory_digits += 1;
+                position += 1;
+                match zero_code {
+                    None => zero_code = Some(group),
+                    Some(zero_code) if zero_code != group => {
+                        return Err(Error::D3131FormatMixedDigitGroups(char_index));
+                    }
+                    _ => {}
+                }
+            }
+            None if c == '#' => position += 1,
+            None => separators.push(GroupingSeparator {
+                position,
+                character: c,
+            }),
+        }
+    }
+
+    // Without any digits this would be a numbering sequence, which are implementation defined,
+    // and none are supported
+    let zero_code = match zero_code {
+        Some(zero_code) => zero_code,
+        None => {
+            return Err(Error::D3130FormatSequenceUnsupported(
+                char_index,
+                picture.to_string(),
+            ))
+        }
+    };
+
+    let grouping = match regular_grouping(&separators) {
+        Some(position) => Grouping::Regular(GroupingSeparator {
+            position,
+            character: separators[0].character,
+        }),
+        None => Grouping::Irregular(separators),
+    };
+
+    Ok(Primary::Decimal {
+        zero_code,
+        mandatory_digits,
+        grouping,
+    })
+}
+
+// Grouping is regular when every separator is the same character and they're all equally
+// spaced, in which case it repeats for numbers longer than the picture
+fn regular_grouping(separators: &[GroupingSeparator]) -> Option<usize> {
+    let first = separators.first()?;
+
+    if separators.iter().any(|s| s.character != first.character) {
+        return None;
+    }
+
+    fn gcd(a: usize, b: usize) -> usize {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+
+    let factor = separators.iter().map(|s| s.position).fold(0, gcd);
+    if factor == 0 {
+        return None;
+    }
+
+    let is_regular =
+        (1..=separators.len()).all(|index| separators.iter().any(|s| s.position == index * factor));
+
+    if is_regular {
+        Some(factor)
+    } else {
+        None
+    }
+}
+
+fn to_letters(value: f64, a: u8) -> String {
+    let mut value = value as u64;
+    let mut letters = Vec::new();
+    while value > 0 {
+        letters.push((a + ((value - 1) % 26) as u8) as char);
+        value = (value - 1) / 26;
+    }
+    letters.iter().rev().collect()
+}
+
+fn to_roman(value: f64) -> String {
+    let mut value = value as u64;
+    let mut roman = String::new();
+    for (numeral_value, numeral) in ROMAN_NUMERALS {
+        while value >= numeral_value {
+            roman.push_str(numeral);
+            value -= numeral_value;
+        }
+    }
+    roman
+}
+
+// Words are built in title case, e.g. "Three Hundred and Twenty-One", and `prev` is set when
+// this is the remainder of a larger number, which needs joining on to it
+fn to_words(value: f64, prev: bool, ordinal: bool) -> String {
+    if value <= 19.0 {
+        let words = if ordinal { ORDINALS } else { FEW };
+        format!(
+            "{}{}",
+            if prev { " and " } else { "" },
+            words[value as usize]
+        )
+    } else if value < 100.0 {
+        let tens = (value / 10.0).floor() as usize;
+        let remainder = value % 10.0;
+        let mut words = format!("{}{}", if prev { " and " } else { "" }, DECADES[tens - 2]);
+        if remainder > 0.0 {
+            words.push('-');
+            words.push_str(&to_words(remainder, false, ordinal));
+        } else if ordinal {
+            // e.g. Twenty becomes Twentieth
+            words.pop();
+            words.push_str("ieth");
+        }
+        words
+    } else if value < 1000.0 {
+        let hundreds = (value / 100.0).floor() as usize;
+        let remainder = value % 100.0;
+        let mut words = format!("{}{} Hundred", if prev { ", " } else { "" }, FEW[hundreds]);
+        if remainder > 0.0 {
+            words.push_str(&to_words(remainder, true, ordinal));
+        } else if ordinal {
+            words.push_str("th");
+        }
+        words
+    } else {
+        // Beyond trillions the largest magnitude is repeated, e.g. "one thousand trillion"
+        let magnitude = ((value.log10() / 3.0).floor() as usize).min(MAGNITUDES.len());
+        let factor = 10f64.powi(magnitude as i32 * 3);
+        let mantissa = (value / factor).floor();
+        let remainder = value - mantissa * factor;
+        let mut words = format!(
+            "{}{} {}",
+            if prev { ", " } else { "" },
+            to_words(mantissa, false, false),
+            MAGNITUDES[magnitude - 1]
+        );
+        if remainder > 0.0 {
+            words.push_str(&to_words(remainder, true, ordinal));
+        } else if ordinal {
+            words.push_str("th");
+        }
+        words
+    }
+}
+
+/// The characters that have a special meaning in a `$formatNumber` picture string, which can be
+/// changed with its options argument, the same as an XPath decimal format.
+pub struct DecimalFormat {
+    pub decimal_separator: char,
+    pub grouping_separator: char,
+    pub exponent_separator: char,
+    pub minus_sign: char,
+    pub percent: String,
+    pub per_mille: String,
+    pub zero_digit: char,
+    pub digit: char,
+    pub pattern_separator: char,
+}
+
+impl Default for DecimalFormat {
+    fn default() -> Self {
+        Self {
+            decimal_separator: '.',
+            grouping_separator: ',',
+            exponent_separator: 'e',
+            minus_sign: '-',
+            percent: String::from("%"),
+            per_mille: String::from("\u{2030}"),
+            zero_digit: '0',
+            digit: '#',
+            pattern_separator: ';',
+        }
+    }
+}
+
+impl DecimalFormat {
+    fn is_decimal_digit(&self, c: char) -> bool {
+        (self.zero_digit..=self.decimal_digit(9)).contains(&c)
+    }
+
+    fn is_digit(&self, c: char) -> bool {
+        c == self.digit || self.is_decimal_digit(c)
+    }
+
+    fn is_active(&self, c: char) -> bool {
+        self.is_digit(c)
+            || c == self.decimal_separator
+            || c == self.exponent_separator
+            || c == self.grouping_separator
+            || c == self.pattern_separator
+    }
+
+    fn decimal_digit(&self, digit: u32) -> char {
+        char::from_u32(self.zero_digit as u32 + digit).unwrap_or(self.zero_digit)
+    }
+}
+
+// A sub-picture split into its parts, where only the characters between the prefix and suffix
+// are active
+struct SubPicture {
+    picture: String,
+    prefix: String,
+    suffix: String,
+    active: Vec<char>,
+    mantissa: Vec<char>,
+    exponent: Option<Vec<char>>,
+    integer: Vec<char>,
+    fraction: Vec<char>,
+}
+
+#[derive(Clone)]
+struct NumberFormat {
+    prefix: String,
+    suffix: String,
+    picture: String,
+    integer_grouping: Vec<usize>,
+    regular_grouping: Option<usize>,
+    fraction_grouping: Vec<usize>,
+    min_integer_digits: usize,
+    scaling_factor: usize,
+    min_fraction_digits: usize,
+    max_fraction_digits: usize,
+    min_exponent_digits: usize,
+}
+
+/// Formats a number using a picture string as described for XPath's `format-number`, e.g.
+/// `#,##0.00` for grouped digits with two decimal places, `0.0e0` for scientific notation or
+/// `0%` for a percentage. A second sub-picture after a `;` is used for negative numbers,
+/// otherwise they're formatted with the first one and a leading minus sign.
+///
+/// The number is rounded to the maximum number of fractional digits in the picture with halves
+/// going to the nearest even digit, the same as XPath's `round-half-to-even`, e.g. `2.345` with
+/// `0.00` is `2.34`.
+pub fn format_number(
+    char_index: usize,
+    value: f64,
+    picture: &str,
+    format: &DecimalFormat,
+) -> Result<String> {
+    let sub_pictures: Vec<&str> = picture.split(format.pattern_separator).collect();
+    if sub_pictures.len() > 2 {
+        return Err(Error::D3080FormatTooManySubPictures(char_index));
+    }
+
+    let mut number_formats = Vec::with_capacity(2);
+    for sub_picture in sub_pictures {
+        let parts = split_sub_picture(sub_picture, format);
+        validate_sub_picture(char_index, &parts, format)?;
+        number_formats.push(analyse_sub_picture(parts, format));
+    }
+
+    let number_format = if value >= 0.0 {
+        &number_formats[0]
+    } else if let Some(number_format) = number_formats.get(1) {
+        number_format
+    } else {
+        number_formats.push(number_formats[0].clone());
+        number_formats[1].prefix.insert(0, format.minus_sign);
+        &number_formats[1]
+    };
+
+    let mut adjusted = value.abs();
+    if number_format.picture.contains(&format.percent) {
+        adjusted *= 100.0;
+    } else if number_format.picture.contains(&format.per_mille) {
+        adjusted *= 1000.0;
+    }
+
+    // With an exponent, the mantissa is scaled to have as many integer digits as are in the
+    // picture, so that mantissa * 10^exponent is the adjusted number
+    let mut mantissa = adjusted;
+    let mut exponent = None;
+    if number_format.min_exponent_digits > 0 {
+        let max_mantissa = 10f64.powi(number_format.scaling_factor as i32);
+        let min_mantissa = 10f64.powi(number_format.scaling_factor as i32 - 1);
+        let mut e = 0i32;
+        if mantissa > 0.0 {
+            while mantissa < min_mantissa {
+                mantissa *= 10.0;
+                e -= 1;
+            }
+            while mantissa > max_mantissa {
+                mantissa /= 10.0;
+                e += 1;
+            }
+        }
+        exponent = Some(e);
+    }
+
+    let mut chars: Vec<char> = round_to_fixed(mantissa, number_format.max_fraction_digits)
+        .chars()
+        .map(|c| to_decimal_digit(c, format))
+        .collect();
+
+    match chars.iter().position(|&c| c == '.') {
+        Some(position) => chars[position] = format.decimal_separator,
+        None => chars.push(format.decimal_separator),
+    }
+
+    // Only the digits required by the picture are kept, so strip the zeroes on either end
+    // before padding back out to the minimum sizes
+    let leading = chars
+        .iter()
+        .take_while(|&&c| c == format.zero_digit)
+        .count();
+    chars.drain(..leading);
+    while chars.last() == Some(&format.zero_digit) {
+        chars.pop();
+    }
+
+    let mut decimal_position = chars
+        .iter()
+        .position(|&c| c == format.decimal_separator)
+        .unwrap_or(chars.len());
+    let fraction_digits = chars.len() - decimal_position - 1;
+    let pad_left = number_format
+        .min_integer_digits
+        .saturating_sub(decimal_position);
+    let pad_right = number_format
+        .min_fraction_digits
+        .saturating_sub(fraction_digits);
+    chars.splice(0..0, std::iter::repeat_n(format.zero_digit, pad_left));
+    chars.extend(std::iter::repeat_n(format.zero_digit, pad_right));
+    decimal_position += pad_left;
+
+    match number_format.regular_grouping {
+        Some(interval) => {
+            let count = decimal_position.saturating_sub(1) / interval;
+            for group in 1..=count {
+                chars.insert(
+                    decimal_position - group * interval,
+                    format.grouping_separator,
+                );
+            }
+            decimal_position += count;
+        }
+        None => {
+            for &position in &number_format.integer_grouping {
+                if position <= decimal_position {
+                    chars.insert(decimal_position - position, format.grouping_separator);
+                    decimal_position += 1;
+                }
+            }
+        }
+    }
+
+    for &position in &number_format.fraction_grouping {
+        let index = decimal_position + position + 1;
+        if index <= chars.len() {
+            chars.insert(index, format.grouping_separator);
+        }
+    }
+
+    if number_format.min_fraction_digits == 0 && decimal_position == chars.len() - 1 {
+        chars.pop();
+    }
+
+    if let Some(exponent) = exponent {
+        let digits: Vec<char> = exponent
+            .unsigned_abs()
+            .to_string()
+            .chars()
+            .map(|c| to_decimal_digit(c, format))
+            .collect();
+        chars.push(format.exponent_separator);
+        if exponent < 0 {
+            chars.push(format.minus_sign);
+        }
+        let pad = number_format
+            .min_exponent_digits
+            .saturating_sub(digits.len());
+        chars.extend(std::iter::repeat_n(format.zero_digit, pad));
+        chars.extend(digits);
+    }
+
+    Ok(format!(
+        "{}{}{}",
+        number_format.prefix,
+        chars.into_iter().collect::<String>(),
+        number_format.suffix
+    ))
+}
+
+fn split_sub_picture(picture: &str, format: &DecimalFormat) -> SubPicture {
+    let chars: Vec<char> = picture.chars().collect();
+
+    // The exponent separator is only active between other active characters, so it can still
+    // be used in a prefix or suffix
+    let is_active = |c: &char| format.is_active(*c) && *c != format.exponent_separator;
+    let start = chars.iter().position(is_active).unwrap_or(chars.len());
+    let end = chars
+        .iter()
+        .rposition(is_active)
+        .map_or(start, |end| end + 1);
+
+    let active = chars[start..end].to_vec();
+
+    let (mantissa, exponent) = match active.iter().position(|&c| c == format.exponent_separator) {
+        Some(position) => (
+            active[..position].to_vec(),
+            Some(active[position + 1..].to_vec()),
+        ),
+        None => (active.clone(), None),
+    };
+
+    let (integer, fraction) = match mantissa.iter().position(|&c| c == format.decimal_separator) {
+        Some(position) => (
+            mantissa[..position].to_vec(),
+            mantissa[position + 1..].to_vec(),
+        ),
+        None => (mantissa.clone(), Vec::new()),
+    };
+
+    SubPicture {
+        picture: picture.to_string(),
+        prefix: chars[..start].iter().collect(),
+        suffix: chars[end..].iter().collect(),
+        active,
+        mantissa,
+        exponent,
+        integer,
+        fraction,
+    }
+}
+
+fn validate_sub_picture(
+    char_index: usize,
+    parts: &SubPicture,
+    format: &DecimalFormat,
+) -> Result<()> {
+    let picture = &parts.picture;
+    let chars: Vec<char> = picture.chars().collect();
+
+    if picture.matches(format.decimal_separator).count() > 1 {
+        return Err(Error::D3081FormatMultipleDecimalSeparators(char_index));
+    }
+
+    let percents = picture.matches(&format.percent).count();
+    let per_milles = picture.matches(&format.per_mille).count();
+    if percents > 1 {
+        return Err(Error::D3082FormatMultiplePercents(char_index));
+    }
+    if per_milles > 1 {
+        return Err(Error::D3083FormatMultiplePerMilles(char_index));
+    }
+    if percents > 0 && per_milles > 0 {
+        return Err(Error::D3084FormatPercentAndPerMille(char_index));
+    }
+
+    if !parts.mantissa.iter().any(|&c| format.is_digit(c)) {
+        return Err(Error::D3085FormatNoMantissaDigits(char_index));
+    }
+
+    if parts.active.iter().any(|&c| !format.is_active(c)) {
+        return Err(Error::D3086FormatPassiveBetweenActive(char_index));
+    }
+
+    match chars.iter().position(|&c| c == format.decimal_separator) {
+        Some(position) => {
+            let before = position.checked_sub(1).map(|index| chars[index]);
+            let after = chars.get(position + 1).copied();
+            if before == Some(format.grouping_separator) || after == Some(format.grouping_separator)
+            {
+                return Err(Error::D3087FormatGroupingNextToDecimal(char_index));
+            }
+        }
+        None => {
+            if parts.integer.last() == Some(&format.grouping_separator) {
+                return Err(Error::D3088FormatGroupingAtEnd(char_index));
+            }
+        }
+    }
+
+    if chars
+        .windows(2)
+        .any(|pair| pair[0] == format.grouping_separator && pair[1] == format.grouping_separator)
+    {
+        return Err(Error::D3089FormatAdjacentGroupings(char_index));
+    }
+
+    if let Some(position) = parts.integer.iter().position(|&c| c == format.digit) {
+        if parts.integer[..position]
+            .iter()
+            .any(|&c| format.is_decimal_digit(c))
+        {
+            return Err(Error::D3090FormatDigitBeforeOptional(char_index));
+        }
+    }
+
+    if let Some(position) = parts.fraction.iter().rposition(|&c| c == format.digit) {
+        if parts.fraction[position..]
+            .iter()
+            .any(|&c| format.is_decimal_digit(c))
+        {
+            return Err(Error::D3091FormatOptionalBeforeDigit(char_index));
+        }
+    }
+
+    if let Some(ref exponent) = parts.exponent {
+        if !exponent.is_empty() && (percents > 0 || per_milles > 0) {
+            return Err(Error::D3092FormatExponentWithPercent(char_index));
+        }
+        if exponent.is_empty() || exponent.iter().any(|&c| !format.is_decimal_digit(c)) {
+            return Err(Error::D3093FormatInvalidExponent(char_index));
+        }
+    }
+
+    Ok(())
+}
+
+fn analyse_sub_picture(parts: SubPicture, format: &DecimalFormat) -> NumberFormat {
+    // Integer grouping positions are counted in digits to the right of each separator, and
+    // fractional ones in digits to the left
+    let integer_grouping: Vec<usize> = parts
+        .integer
+        .iter()
+        .enumerate()
+        .filter(|(_, &c)| c == format.grouping_separator)
+        .map(|(index, _)| {
+            parts.integer[index..]
+                .iter()
+                .filter(|&&c| format.is_digit(c))
+                .count()
+        })
+        .collect();
+    let fraction_grouping: Vec<usize> = parts
+        .fraction
+        .iter()
+        .enumerate()
+        .filter(|(_, &c)| c == format.grouping_separator)
+        .map(|(index, _)| {
+            parts.fraction[..index]
+                .iter()
+                .filter(|&&c| format.is_digit(c))
+                .count()
+        })
+        .collect();
+
+    let separators: Vec<GroupingSeparator> = integer_grouping
+        .iter()
+        .map(|&position| GroupingSeparator {
+            position,
+            character: format.grouping_separator,
+        })
+        .collect();
+    let regular_grouping = regular_grouping(&separators);
+
+    let mut min_integer_digits = parts
+        .integer
+        .iter()
+        .filter(|&&c| format.is_decimal_digit(c))
+        .count();
+    let scaling_factor = min_integer_digits;
+    let mut min_fraction_digits = parts
+        .fraction
+        .iter()
+        .filter(|&&c| format.is_decimal_digit(c))
+        .count();
+    let mut max_fraction_digits = parts
+        .fraction
+        .iter()
+        .filter(|&&c| format.is_digit(c))
+        .count();
+
+    let has_exponent = parts.exponent.is_some();
+    if min_integer_digits == 0 && max_fraction_digits == 0 {
+        if has_exponent {
+            min_fraction_digits = 1;
+            max_fraction_digits = 1;
+        } else {
+            min_integer_digits = 1;
+        }
+    }
+    if has_exponent && min_integer_digits == 0 && parts.integer.contains(&format.digit) {
+        min_integer_digits = 1;
+    }
+    if min_integer_digits == 0 && min_fraction_digits == 0 {
+        min_fraction_digits = 1;
+    }
+
+    let min_exponent_digits = parts.exponent.as_ref().map_or(0, |exponent| {
+        exponent
+            .iter()
+            .filter(|&&c| format.is_decimal_digit(c))
+            .count()
+    });
+
+    NumberFormat {
+        prefix: parts.prefix,
+        suffix: parts.suffix,
+        picture: parts.picture,
+        integer_grouping,
+        regular_grouping,
+        fraction_grouping,
+        min_integer_digits,
+        scaling_factor,
+        min_fraction_digits,
+        max_fraction_digits,
+        min_exponent_digits,
+    }
+}
+
+fn to_decimal_digit(c: char, format: &DecimalFormat) -> char {
+    match c.to_digit(10) {
+        Some(digit) => format.decimal_digit(digit),
+        None => c,
+    }
+}
+
+// Writes a non-negative number with exactly `places` fractional digits, rounding half to even.
+// This works on the shortest decimal digits of the number, so e.g. 2.345 is treated as exactly
+// that rather than the binary fraction just below it.
+fn round_to_fixed(value: f64, places: usize) -> String {
+    let repr = format!("{:e}", value);
+    let (mantissa, exponent) = repr.split_once('e').unwrap_or((&repr, "0"));
+    let mut digits: Vec<u8> = mantissa
+        .bytes()
+        .filter(u8::is_ascii_digit)
+        .map(|digit| digit - b'0')
+        .collect();
+
+    // The number of digits before the decimal point, padded out so there's at least one
+    let point = exponent.parse::<i32>().unwrap_or(0) + 1;
+    let lead = (1 - point).max(0) as usize;
+    digits.splice(0..0, std::iter::repeat_n(0, lead));
+    let mut point = point as usize + lead;
+
+    let keep = point + places;
+    if digits.len() > keep {
+        let rest = digits.split_off(keep);
+        let round_up = match rest[0] {
+            6..=9 => true,
+            5 => rest[1..].iter().any(|&digit| digit > 0) || digits[keep - 1] % 2 == 1,
+            _ => false,
+        };
+        if round_up {
+            let mut index = keep;
+            loop {
+                if index == 0 {
+                    digits.insert(0, 1);
+                    point += 1;
+                    break;
+                }
+                index -= 1;
+                if digits[index] == 9 {
+                    digits[index] = 0;
+                } else {
+                    digits[index] += 1;
+                    break;
+                }
+            }
+        }
+    } else {
+        digits.resize(keep, 0);
+    }
+
+    let mut fixed: String = digits[..point]
+        .iter()
+        .map(|&digit| (b'0' + digit) as char)
+        .collect();
+    if places > 0 {
+        fixed.push('.');
+        fixed.extend(digits[point..].iter().map(|&digit| (b'0' + digit) as char));
+    }
+    fixed
+}
@@ -1,10 +1,12 @@
 use bumpalo::Bump;
-use lazy_static;
+use chrono::SecondsFormat;
+use indexmap::IndexSet;
 
 use jsonata_errors::{Error, Result};
 use jsonata_signature_macro::signature;
 
 use super::evaluator::Evaluator;
+use super::format::{format_integer, format_number, DecimalFormat};
 use super::frame::Frame;
 use super::value::{ArrayFlags, Value};
 
@@ -27,6 +29,22 @@ impl<'a, 'e> FunctionContext<'a, 'e> {
         self.evaluator
             .apply_function(self.char_index, self.input, proc, args, &self.frame)
     }
+
+    /// Invokes the callback of a higher-order function such as `$map` or `$reduce`. Callbacks
+    /// don't need to declare every parameter they're offered, so `args` are passed in order up
+    /// to the callback's arity, but the first is always passed.
+    pub fn evaluate_callback(
+        &self,
+        func: &'a Value<'a>,
+        args: &[&'a Value<'a>],
+    ) -> Result<&'a Value<'a>> {
+        let count = func.arity().clamp(1, args.len());
+        let callback_args = Value::array_with_capacity(self.arena, count, ArrayFlags::empty());
+        for arg in &args[..count] {
+            callback_args.push(arg);
+        }
+        self.evaluate_function(func, callback_args)
+    }
 }
 
 pub fn fn_lookup_internal<'a, 'e>(
@@ -56,6 +74,8 @@ pub fn fn_lookup_internal<'a, 'e>(
     }
 }
 
+/// Looks up a key in an object, or in each object in an array. Anything else, including
+/// `null` and other scalars, has no keys to look up and gives undefined rather than an error.
 #[signature("<x-s:x>")]
 pub fn fn_lookup<'a, 'e>(
     context: FunctionContext<'a, 'e>,
@@ -65,7 +85,7 @@ pub fn fn_lookup<'a, 'e>(
     if !key.is_string() {
         Err(Error::T0410ArgumentNotValid(
             context.char_index,
-            1,
+            2,
             context.name.to_string(),
         ))
     } else {
@@ -73,6 +93,106 @@ pub fn fn_lookup<'a, 'e>(
     }
 }
 
+#[signature("<x-:a<s>>")]
+pub fn fn_keys<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    input: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    // Keys are returned in insertion order; for an array of objects this is the order each key
+    // was first seen across the array
+    let mut keys = IndexSet::new();
+
+    match input {
+        Value::Object(..) => keys.extend(input.entries().map(|(key, _)| key)),
+        Value::Array(..) => input
+            .members()
+            .filter(|member| member.is_object())
+            .for_each(|member| keys.extend(member.entries().map(|(key, _)| key))),
+        _ => {}
+    }
+
+    if keys.is_empty() {
+        return Ok(Value::undefined());
+    }
+
+    let result = Value::array_with_capacity(context.arena, keys.len(), ArrayFlags::SEQUENCE);
+    for key in keys {
+        result.push(Value::string(context.arena, key.clone()));
+    }
+
+    Ok(result)
+}
+
+/// Splits an object into an array of objects with one entry each, in key order. Each object in
+/// an array is spread into the same result, and anything else is returned as it is.
+#[signature("<x-:a<o>>")]
+pub fn fn_spread<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    arg: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    fn spread_into<'a>(arena: &'a Bump, result: &mut Value<'a>, value: &'a Value<'a>) {
+        match value {
+            Value::Array(..) => value
+                .members()
+                .for_each(|member| spread_into(arena, result, member)),
+            Value::Object(..) => {
+                for (key, value) in value.entries() {
+                    let entry = Value::object_with_capacity(arena, 1);
+                    entry.insert(key, value);
+                    result.push(entry);
+                }
+            }
+            Value::Undefined => {}
+            _ => result.push(value),
+        }
+    }
+
+    if !arg.is_array() && !arg.is_object() {
+        return Ok(arg);
+    }
+
+    let result = Value::array(context.arena, ArrayFlags::SEQUENCE);
+    spread_into(context.arena, result, arg);
+
+    Ok(result)
+}
+
+#[signature("<a<o>:o>")]
+pub fn fn_merge<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    objects: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if objects.is_undefined() {
+        return Ok(Value::undefined());
+    }
+
+    let objects = Value::wrap_in_array_if_needed(context.arena, objects, ArrayFlags::empty());
+
+    // Undefined members (e.g. from a path that didn't match) are skipped, anything else that
+    // isn't an object is an error
+    if !objects
+        .members()
+        .all(|object| object.is_undefined() || object.is_object())
+    {
+        return Err(Error::T0412ArgumentMustBeArrayOfType(
+            context.char_index,
+            1,
+            context.name.to_string(),
+            "object".to_string(),
+        ));
+    }
+
+    let result = Value::object(context.arena);
+
+    for object in objects.members().filter(|object| object.is_object()) {
+        for (key, value) in object.entries() {
+            result.insert(key, value);
+        }
+    }
+
+    Ok(result)
+}
+
 // TODO: Added this to make `evaluate_unary_op` compile, probably can be factored out
 pub fn fn_append_internal<'a, 'e>(
     context: FunctionContext<'a, 'e>,
@@ -181,7 +301,8 @@ pub fn fn_boolean<'a, 'e>(
         | Value::NativeFn0 { .. }
         | Value::NativeFn1 { .. }
         | Value::NativeFn2 { .. }
-        | Value::NativeFn3 { .. } => Value::bool(context.arena, false),
+        | Value::NativeFn3 { .. }
+        | Value::NativeFn4 { .. } => Value::bool(context.arena, false),
     })
 }
 
@@ -208,20 +329,14 @@ pub fn fn_filter<'a, 'e>(
     let result = Value::array(context.arena, ArrayFlags::SEQUENCE);
 
     for (index, item) in arr.members().enumerate() {
-        let args = Value::array(context.arena, ArrayFlags::empty());
-        let arity = func.arity();
+        // The predicate can be any function, so its result is cast to a boolean the same way
+        // `$boolean` would, rather than expecting a boolean back. `$boolean` leaves undefined as
+        // undefined, e.g. when the predicate reads a missing field, which drops the member
+        let include =
+            context.evaluate_callback(func, &[item, Value::number(context.arena, index), arr])?;
+        let include = fn_boolean(context.clone(), include)?;
 
-        args.push(item);
-        if arity >= 2 {
-            args.push(Value::number(context.arena, index));
-        }
-        if arity >= 3 {
-            args.push(&*arr);
-        }
-
-        let include = context.evaluate_function(func, args)?;
-
-        if include.is_truthy() {
+        if include.is_bool() && include.as_bool() {
             result.push(item);
         }
     }
@@ -229,96 +344,224 @@ pub fn fn_filter<'a, 'e>(
     Ok(result)
 }
 
-#[signature("<x-b?:s>")]
-pub fn fn_string<'a, 'e>(
+/// Returns the one member of an array that satisfies the predicate, or the one member of the
+/// array if there's no predicate. An undefined input has nothing to match and is returned as
+/// undefined, but any array, including an empty one, must have exactly one match.
+#[signature("<af?>")]
+pub fn fn_single<'a, 'e>(
     context: FunctionContext<'a, 'e>,
-    arg: &'a Value<'a>,
+    arr: &'a Value<'a>,
+    func: &'a Value<'a>,
 ) -> Result<&'a Value<'a>> {
-    if arg.is_undefined() {
+    if arr.is_undefined() {
         return Ok(Value::undefined());
     }
 
-    if arg.is_string() {
-        Ok(arg)
-    } else if arg.is_function() {
-        Ok(Value::string(context.arena, String::from("")))
+    let arr = Value::wrap_in_array_if_needed(context.arena, arr, ArrayFlags::empty());
 
-    // TODO: Check for infinite numbers
-    // } else if arg.is_number() && arg.is_infinite() {
-    //     // TODO: D3001
-    //     unreachable!()
+    if !func.is_undefined() && !func.is_function() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            2,
+            context.name.to_string(),
+        ));
+    }
 
-    // TODO: pretty printing
-    } else {
-        Ok(Value::string(context.arena, arg.dump()))
+    let mut result = None;
+
+    for (index, item) in arr.members().enumerate() {
+        let matches = if func.is_undefined() {
+            true
+        } else {
+            let matches = context
+                .evaluate_callback(func, &[item, Value::number(context.arena, index), arr])?;
+            let matches = fn_boolean(context.clone(), matches)?;
+            matches.is_bool() && matches.as_bool()
+        };
+
+        if matches {
+            if result.is_some() {
+                return Err(Error::D3138SingleTooMany(context.char_index));
+            }
+            result = Some(*item);
+        }
     }
+
+    result.ok_or(Error::D3139SingleTooFew(context.char_index))
 }
 
-#[signature("<a:n>")]
-pub fn fn_count<'a, 'e>(
+/// Calls the function for each member of the array, returning a sequence of the results.
+///
+/// A sequence returned by the function, e.g. from a path, is flattened one level into the
+/// result, while an array constructor is kept as a single member, so `function($v){ $v.items }`
+/// adds each item but `function($v){ [$v, 1] }` adds one array.
+#[signature("<af>")]
+pub fn fn_map<'a, 'e>(
     context: FunctionContext<'a, 'e>,
-    arg: &'a Value<'a>,
+    arr: &'a Value<'a>,
+    func: &'a Value<'a>,
 ) -> Result<&'a Value<'a>> {
-    Ok(Value::number(
-        context.arena,
-        if arg.is_undefined() {
-            0
-        } else if arg.is_array() {
-            arg.len()
+    if arr.is_undefined() {
+        return Ok(Value::undefined());
+    }
+
+    let arr = Value::wrap_in_array_if_needed(context.arena, arr, ArrayFlags::empty());
+
+    if !func.is_function() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            2,
+            context.name.to_string(),
+        ));
+    }
+
+    let result = Value::array(context.arena, ArrayFlags::SEQUENCE);
+
+    for (index, item) in arr.members().enumerate() {
+        let mapped =
+            context.evaluate_callback(func, &[item, Value::number(context.arena, index), arr])?;
+
+        // Undefined results are dropped from the output, rather than leaving holes. Sequences
+        // are flattened one level into the output, the same way path steps flatten their results
+        if mapped.is_undefined() {
+            continue;
+        } else if mapped.has_flags(ArrayFlags::SEQUENCE) {
+            mapped.members().for_each(|member| result.push(member));
         } else {
-            1
-        },
-    ))
-}
+            result.push(mapped);
+        }
+    }
 
-#[signature("<x-:b>")]
-pub fn fn_not<'a, 'e>(
-    context: FunctionContext<'a, 'e>,
-    arg: &'a Value<'a>,
-) -> Result<&'a Value<'a>> {
-    Ok(if arg.is_undefined() {
-        Value::undefined()
-    } else {
-        Value::bool(context.arena, !arg.is_truthy())
-    })
+    Ok(result)
 }
 
-#[signature("<s-:s>")]
-pub fn fn_lowercase<'a, 'e>(
+/// Reduces an array with a callback taking `($accumulator, $value, $index, $array)`, where only
+/// the first two are required. `$index` is the position of `$value` in the array, not a count
+/// of the callback invocations: without an initial value the first member seeds the
+/// accumulator, so the first invocation sees index 1, while with an initial value it sees 0.
+#[signature("<afj?:j>")]
+pub fn fn_reduce<'a, 'e>(
     context: FunctionContext<'a, 'e>,
-    arg: &'a Value<'a>,
+    arr: &'a Value<'a>,
+    func: &'a Value<'a>,
+    init: &'a Value<'a>,
 ) -> Result<&'a Value<'a>> {
-    Ok(if !arg.is_string() {
-        Value::undefined()
+    if arr.is_undefined() {
+        return Ok(Value::undefined());
+    }
+
+    let arr = Value::wrap_in_array_if_needed(context.arena, arr, ArrayFlags::empty());
+
+    if !func.is_function() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            2,
+            context.name.to_string(),
+        ));
+    }
+
+    // The callback needs to accept at least the accumulator and the current value
+    if func.arity() < 2 {
+        return Err(Error::D3050ReduceInsufficientArgs(context.char_index));
+    }
+
+    // Without an initial value the first member seeds the accumulator, so a single member is
+    // returned as-is without invoking the callback, and an empty array reduces to undefined
+    let (mut result, start) = if init.is_undefined() && !arr.is_empty() {
+        (arr.get_member(0), 1)
     } else {
-        Value::string(context.arena, arg.as_str().to_lowercase())
-    })
+        (init, 0)
+    };
+
+    for index in start..arr.len() {
+        result = context.evaluate_callback(
+            func,
+            &[
+                result,
+                arr.get_member(index),
+                Value::number(context.arena, index),
+                arr,
+            ],
+        )?;
+    }
+
+    Ok(result)
 }
 
-#[signature("<s-:s>")]
-pub fn fn_uppercase<'a, 'e>(
+/// Returns an object with only the entries for which the predicate, called with
+/// `($value, $key, $object)`, is truthy. An object with no matching entries gives undefined.
+#[signature("<o-f:o>")]
+pub fn fn_sift<'a, 'e>(
     context: FunctionContext<'a, 'e>,
-    arg: &'a Value<'a>,
+    obj: &'a Value<'a>,
+    func: &'a Value<'a>,
 ) -> Result<&'a Value<'a>> {
-    if !arg.is_string() {
-        Ok(Value::undefined())
+    // With only the function given, it's applied to the input, e.g. `Address.$sift(...)`
+    let (obj, func) = if func.is_undefined() && obj.is_function() {
+        (context.input, obj)
     } else {
-        Ok(Value::string(context.arena, arg.as_str().to_uppercase()))
+        (obj, func)
+    };
+
+    if obj.is_undefined() {
+        return Ok(Value::undefined());
+    }
+
+    if !obj.is_object() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            1,
+            context.name.to_string(),
+        ));
+    }
+
+    if !func.is_function() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            2,
+            context.name.to_string(),
+        ));
+    }
+
+    let result = Value::object(context.arena);
+
+    for (key, value) in obj.entries() {
+        let include =
+            context.evaluate_callback(func, &[value, Value::string(context.arena, key), obj])?;
+        let include = fn_boolean(context.clone(), include)?;
+
+        if include.is_bool() && include.as_bool() {
+            result.insert(key, value);
+        }
+    }
+
+    if result.is_empty() {
+        return Ok(Value::undefined());
     }
+
+    Ok(result)
 }
 
-#[signature("<s-nn?:s>")]
-pub fn fn_substring<'a, 'e>(
+/// Calls the function with `($value, $key, $object)` for each entry of an object, returning the
+/// results in the object's key order. Undefined results are dropped.
+#[signature("<o-f:a>")]
+pub fn fn_each<'a, 'e>(
     context: FunctionContext<'a, 'e>,
-    string: &'a Value<'a>,
-    start: &'a Value<'a>,
-    length: &'a Value<'a>,
+    obj: &'a Value<'a>,
+    func: &'a Value<'a>,
 ) -> Result<&'a Value<'a>> {
-    if string.is_undefined() {
+    // With only the function given, it's applied to the input, e.g. `Address.$each(...)`
+    let (obj, func) = if func.is_undefined() && obj.is_function() {
+        (context.input, obj)
+    } else {
+        (obj, func)
+    };
+
+    if obj.is_undefined() {
         return Ok(Value::undefined());
     }
 
-    if !string.is_string() {
+    if !obj.is_object() {
         return Err(Error::T0410ArgumentNotValid(
             context.char_index,
             1,
@@ -326,7 +569,7 @@ pub fn fn_substring<'a, 'e>(
         ));
     }
 
-    if !start.is_number() {
+    if !func.is_function() {
         return Err(Error::T0410ArgumentNotValid(
             context.char_index,
             2,
@@ -334,56 +577,891 @@ pub fn fn_substring<'a, 'e>(
         ));
     }
 
-    let string = string.as_str();
+    let result = Value::array(context.arena, ArrayFlags::SEQUENCE);
 
-    // Scan the string chars for the actual number of characters.
-    // NOTE: Chars are not grapheme clusters, so for some inputs like "नमस्ते" we will get 6
-    //       as it will include the diacritics.
-    //       See: https://doc.rust-lang.org/nightly/book/ch08-02-strings.html
-    let len = string.chars().count() as isize;
-    let mut start = start.as_isize();
+    for (key, value) in obj.entries() {
+        let mapped =
+            context.evaluate_callback(func, &[value, Value::string(context.arena, key), obj])?;
+        if !mapped.is_undefined() {
+            result.push(mapped);
+        }
+    }
 
-    // If start is negative and runs off the front of the string
-    if len + start < 0 {
-        start = 0;
+    Ok(result)
+}
+
+/// Sorts an array, stably, into a new array. Without a comparator all members must be numbers
+/// or all strings. A comparator `function($l, $r)` returns true when `$l` should come after `$r`.
+/// Its result is cast the same way as by `$boolean`, so anything falsy, including undefined,
+/// means the two are left in their current order rather than being an error.
+#[signature("<af?:a>")]
+pub fn fn_sort<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    arr: &'a Value<'a>,
+    comparator: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if arr.is_undefined() {
+        return Ok(Value::undefined());
     }
 
-    // If start is negative, count from the end of the string
-    let start = if start < 0 { len + start } else { start };
+    let arr = Value::wrap_in_array_if_needed(context.arena, arr, ArrayFlags::empty());
 
-    if length.is_undefined() {
-        Ok(Value::string(
-            context.arena,
-            string[start as usize..].to_string(),
-        ))
+    if arr.len() <= 1 {
+        return Ok(arr);
+    }
+
+    let members = arr.members().copied().collect::<Vec<_>>();
+
+    let sorted = if comparator.is_undefined() {
+        let all_numbers = arr.members().all(|member| member.is_number());
+        let all_strings = arr.members().all(|member| member.is_string());
+
+        if !all_numbers && !all_strings {
+            return Err(Error::D3070InvalidDefaultSort(context.char_index));
+        }
+
+        // Strings are ordered the same way the `<` and `>` operators order them, so numeric
+        // strings are never coerced: ["10", "2", "1"] sorts to ["1", "10", "2"]
+        merge_sort(members, &mut |a, b| {
+            Ok(if all_numbers {
+                a.as_f64() > b.as_f64()
+            } else {
+                a.as_str() > b.as_str()
+            })
+        })?
     } else {
-        if !length.is_number() {
+        if !comparator.is_function() {
             return Err(Error::T0410ArgumentNotValid(
                 context.char_index,
-                3,
+                2,
                 context.name.to_string(),
             ));
         }
 
-        let length = length.as_isize();
-        if length < 0 {
-            Ok(Value::string(context.arena, String::from("")))
-        } else {
-            let end = if start >= 0 {
-                (start + length) as usize
-            } else {
-                (len + start + length) as usize
-            };
+        merge_sort(members, &mut |a, b| {
+            let args = Value::array(context.arena, ArrayFlags::empty());
+            args.push(a);
+            args.push(b);
+            Ok(context.evaluate_function(comparator, args)?.is_truthy())
+        })?
+    };
 
-            let substring = string
+    let result = Value::array_with_capacity(context.arena, sorted.len(), ArrayFlags::empty());
+    sorted.into_iter().for_each(|member| result.push(member));
+
+    Ok(result)
+}
+
+#[signature("<x:x>")]
+pub fn fn_distinct<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    arr: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if !arr.is_array() || arr.len() <= 1 {
+        return Ok(arr);
+    }
+
+    let flags = if arr.has_flags(ArrayFlags::SEQUENCE) {
+        ArrayFlags::SEQUENCE
+    } else {
+        ArrayFlags::empty()
+    };
+
+    // The first occurrence of each value is kept as-is rather than copied, so anything that
+    // follows sees exactly the same value as the input held
+    let mut distinct: Vec<&'a Value<'a>> = Vec::with_capacity(arr.len());
+    for member in arr.members() {
+        if !distinct.contains(member) {
+            distinct.push(member);
+        }
+    }
+
+    let result = Value::array_with_capacity(context.arena, distinct.len(), flags);
+    distinct.into_iter().for_each(|member| result.push(member));
+
+    Ok(result)
+}
+
+/// A stable, top-down merge sort. `swap(a, b)` returns true when `a` should be placed after `b`,
+/// which is the contract of a JSONata sort comparator. Each merge compares the heads of its two
+/// halves only, so sorting `n` members invokes the comparator at most `n * ceil(log2(n))` times.
+pub(crate) fn merge_sort<T, F>(mut items: Vec<T>, swap: &mut F) -> Result<Vec<T>>
+where
+    T: Copy,
+    F: FnMut(T, T) -> Result<bool>,
+{
+    if items.len() < 2 {
+        return Ok(items);
+    }
+
+    let right = items.split_off(items.len() / 2);
+    let left = merge_sort(items, swap)?;
+    let right = merge_sort(right, swap)?;
+
+    let mut result = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+
+    while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+        // Only take from the right when the comparator asks for it, which keeps equal members in
+        // their original order
+        if swap(*l, *r)? {
+            result.push(*r);
+            right.next();
+        } else {
+            result.push(*l);
+            left.next();
+        }
+    }
+
+    result.extend(left);
+    result.extend(right);
+
+    Ok(result)
+}
+
+#[signature("<x:s>")]
+pub fn fn_type<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    arg: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    // An undefined argument has no type, so the result is undefined too rather than a string
+    let name = match arg {
+        Value::Undefined => return Ok(Value::undefined()),
+        Value::Null => "null",
+        Value::Number(..) => "number",
+        Value::String(..) => "string",
+        Value::Bool(..) => "boolean",
+        // Sequences are arrays built up during evaluation, and are only an internal distinction
+        Value::Array(..) => "array",
+        Value::Object(..) => "object",
+        Value::Lambda { .. }
+        | Value::NativeFn0(..)
+        | Value::NativeFn1(..)
+        | Value::NativeFn2(..)
+        | Value::NativeFn3(..)
+        | Value::NativeFn4(..) => "function",
+    };
+
+    Ok(Value::string(context.arena, String::from(name)))
+}
+
+/// Casts the argument to a string. Strings are returned as-is, and anything else is written
+/// out as JSON, pretty-printed with two space indentation when `prettify` is true.
+#[signature("<x-b?:s>")]
+pub fn fn_string<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    arg: &'a Value<'a>,
+    prettify: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if arg.is_undefined() {
+        return Ok(Value::undefined());
+    }
+
+    if !prettify.is_undefined() && !prettify.is_bool() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            2,
+            context.name.to_string(),
+        ));
+    }
+
+    if arg.is_string() {
+        Ok(arg)
+    } else if arg.is_function() {
+        // JSONata casts functions to an empty string rather than throwing, and this keeps that
+        // for compatibility, the same as for functions nested in objects and arrays below
+        Ok(Value::string(context.arena, String::from("")))
+
+    // TODO: Check for infinite numbers
+    // } else if arg.is_number() && arg.is_infinite() {
+    //     // TODO: D3001
+    //     unreachable!()
+    } else {
+        let value = string_value(context.arena, arg);
+        let json = if prettify.is_bool() && prettify.as_bool() {
+            value.pretty(2)
+        } else {
+            value.dump()
+        };
+        Ok(Value::string(context.arena, json))
+    }
+}
+
+/// Prepares a composite value to be written out as valid JSON by `$string`, following
+/// JSONata's conventions: functions are written as empty strings, and undefined members are
+/// left out of objects and written as `null` in arrays.
+fn string_value<'a>(arena: &'a Bump, value: &'a Value<'a>) -> &'a Value<'a> {
+    match value {
+        Value::Array(..) => {
+            let result = Value::array_with_capacity(arena, value.len(), ArrayFlags::empty());
+            for member in value.members() {
+                if member.is_undefined() {
+                    result.push(Value::null(arena));
+                } else {
+                    result.push(string_value(arena, member));
+                }
+            }
+            result
+        }
+        Value::Object(..) => {
+            let result = Value::object(arena);
+            for (key, member) in value.entries() {
+                if !member.is_undefined() {
+                    result.insert(key, string_value(arena, member));
+                }
+            }
+            result
+        }
+        _ if value.is_function() => Value::string(arena, String::new()),
+        _ => value,
+    }
+}
+
+#[signature("<a:n>")]
+pub fn fn_count<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    arg: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    // Arrays are counted by their length, so a path ending on an array in the input is counted
+    // without its members being copied into a sequence first
+    Ok(Value::number(
+        context.arena,
+        if arg.is_undefined() {
+            0
+        } else if arg.is_array() {
+            arg.len()
+        } else {
+            1
+        },
+    ))
+}
+
+/// Returns whether the argument has a value. Paths that don't match are undefined rather than
+/// an error, however deep the missing step is, so `$exists(a.b.c)` is false when `a.b` is
+/// missing, but true when `a.b.c` is `null`.
+#[signature("<x:b>")]
+pub fn fn_exists<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    arg: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    Ok(Value::bool(context.arena, !arg.is_undefined()))
+}
+
+#[signature("<x-:b>")]
+pub fn fn_not<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    arg: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    Ok(if arg.is_undefined() {
+        Value::undefined()
+    } else {
+        Value::bool(context.arena, !arg.is_truthy())
+    })
+}
+
+#[signature("<s-:s>")]
+pub fn fn_lowercase<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    arg: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    Ok(if !arg.is_string() {
+        Value::undefined()
+    } else {
+        Value::string(context.arena, arg.as_str().to_lowercase())
+    })
+}
+
+/// Returns the number of characters in a string, counting Unicode code points rather than bytes,
+/// so `$length("😀")` is 1. Combining characters count separately from the character they
+/// combine with.
+#[signature("<s-:n>")]
+pub fn fn_length<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    arg: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if arg.is_undefined() {
+        return Ok(Value::undefined());
+    }
+
+    if !arg.is_string() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            1,
+            context.name.to_string(),
+        ));
+    }
+
+    Ok(Value::number(context.arena, arg.as_str().chars().count()))
+}
+
+#[signature("<s-:s>")]
+pub fn fn_uppercase<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    arg: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if !arg.is_string() {
+        Ok(Value::undefined())
+    } else {
+        Ok(Value::string(context.arena, arg.as_str().to_uppercase()))
+    }
+}
+
+#[signature("<s-:s>")]
+pub fn fn_trim<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    arg: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if !arg.is_string() {
+        return Ok(Value::undefined());
+    }
+
+    // Normalizes the whitespace the same as XPath's normalize-space, except that any Unicode
+    // whitespace counts rather than just spaces, tabs and newlines
+    let trimmed = arg
+        .as_str()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Ok(Value::string(context.arena, trimmed))
+}
+
+#[signature("<s-nn?:s>")]
+pub fn fn_substring<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    string: &'a Value<'a>,
+    start: &'a Value<'a>,
+    length: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if string.is_undefined() {
+        return Ok(Value::undefined());
+    }
+
+    if !string.is_string() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            1,
+            context.name.to_string(),
+        ));
+    }
+
+    if !start.is_number() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            2,
+            context.name.to_string(),
+        ));
+    }
+
+    let string = string.as_str();
+
+    // Scan the string chars for the actual number of characters.
+    // NOTE: Chars are not grapheme clusters, so for some inputs like "नमस्ते" we will get 6
+    //       as it will include the diacritics.
+    //       See: https://doc.rust-lang.org/nightly/book/ch08-02-strings.html
+    let len = string.chars().count() as isize;
+    let mut start = start.as_isize();
+
+    // If start is negative and runs off the front of the string
+    if start < 0 && len + start < 0 {
+        start = 0;
+    }
+
+    // If start is negative, count from the end of the string
+    let start = if start < 0 { len + start } else { start };
+
+    // Without a length the substring runs to the end of the string. The start is a character
+    // offset which may be past the end, so it can't be used to slice the string directly.
+    if length.is_undefined() {
+        Ok(Value::string(
+            context.arena,
+            string.chars().skip(start as usize).collect::<String>(),
+        ))
+    } else {
+        if !length.is_number() {
+            return Err(Error::T0410ArgumentNotValid(
+                context.char_index,
+                3,
+                context.name.to_string(),
+            ));
+        }
+
+        // A negative length is an empty substring, it doesn't count back from the end of the
+        // string the way a negative start does
+        let length = length.as_isize();
+        if length < 0 {
+            Ok(Value::string(context.arena, String::from("")))
+        } else {
+            // Lengths running past the end of the string are clamped to it, which also keeps
+            // huge lengths from overflowing
+            let length = length.min((len - start).max(0));
+
+            let substring = string
                 .chars()
                 .skip(start as usize)
-                .take(end - start as usize)
+                .take(length as usize)
                 .collect::<String>();
 
-            Ok(Value::string(context.arena, substring))
+            Ok(Value::string(context.arena, substring))
+        }
+    }
+}
+
+#[signature("<s-s:s>")]
+pub fn fn_substring_before<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    string: &'a Value<'a>,
+    chars: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if string.is_undefined() {
+        return Ok(Value::undefined());
+    }
+
+    if !string.is_string() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            1,
+            context.name.to_string(),
+        ));
+    }
+
+    if !chars.is_string() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            2,
+            context.name.to_string(),
+        ));
+    }
+
+    let string = string.as_str();
+
+    // An empty separator is found at the very start, so nothing comes before it
+    match string.find(&*chars.as_str()) {
+        Some(index) => Ok(Value::string(context.arena, string[..index].to_string())),
+        None => Ok(Value::string(context.arena, string.to_string())),
+    }
+}
+
+#[signature("<s-s:s>")]
+pub fn fn_substring_after<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    string: &'a Value<'a>,
+    chars: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if string.is_undefined() {
+        return Ok(Value::undefined());
+    }
+
+    if !string.is_string() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            1,
+            context.name.to_string(),
+        ));
+    }
+
+    if !chars.is_string() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            2,
+            context.name.to_string(),
+        ));
+    }
+
+    let string = string.as_str();
+    let chars = chars.as_str();
+
+    // An empty separator is found at the very start, so the whole string comes after it
+    match string.find(&*chars) {
+        Some(index) => Ok(Value::string(
+            context.arena,
+            string[index + chars.len()..].to_string(),
+        )),
+        None => Ok(Value::string(context.arena, string.to_string())),
+    }
+}
+
+#[signature("<s-(sf):b>")]
+pub fn fn_contains<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    string: &'a Value<'a>,
+    pattern: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if string.is_undefined() {
+        return Ok(Value::undefined());
+    }
+
+    if !string.is_string() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            1,
+            context.name.to_string(),
+        ));
+    }
+
+    // TODO: Regular expression patterns
+    if !pattern.is_string() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            2,
+            context.name.to_string(),
+        ));
+    }
+
+    Ok(Value::bool(
+        context.arena,
+        string.as_str().contains(&*pattern.as_str()),
+    ))
+}
+
+#[signature("<s-ns?:s>")]
+pub fn fn_pad<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    string: &'a Value<'a>,
+    width: &'a Value<'a>,
+    chars: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if string.is_undefined() {
+        return Ok(Value::undefined());
+    }
+
+    if !string.is_string() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            1,
+            context.name.to_string(),
+        ));
+    }
+
+    if !width.is_number() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            2,
+            context.name.to_string(),
+        ));
+    }
+
+    let chars = if chars.is_undefined() {
+        String::from(" ")
+    } else if chars.is_string() {
+        chars.as_str().to_string()
+    } else {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            3,
+            context.name.to_string(),
+        ));
+    };
+
+    // An empty padding string pads with spaces instead
+    let chars = if chars.is_empty() {
+        String::from(" ")
+    } else {
+        chars
+    };
+
+    let string = string.as_str();
+    let width = width.as_isize();
+
+    // A positive width pads on the right, a negative width on the left. Nothing is added when the
+    // string is already at least as long as the width, including a width of zero.
+    let pad_length = width.unsigned_abs().saturating_sub(string.chars().count());
+    if pad_length == 0 {
+        return Ok(Value::string(context.arena, string.to_string()));
+    }
+
+    let padding = chars.chars().cycle().take(pad_length).collect::<String>();
+    let result = if width > 0 {
+        format!("{}{}", string, padding)
+    } else {
+        format!("{}{}", padding, string)
+    };
+
+    Ok(Value::string(context.arena, result))
+}
+
+#[signature("<s-(sf)n?:a<s>>")]
+pub fn fn_split<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    string: &'a Value<'a>,
+    separator: &'a Value<'a>,
+    limit: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if string.is_undefined() {
+        return Ok(Value::undefined());
+    }
+
+    if !string.is_string() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            1,
+            context.name.to_string(),
+        ));
+    }
+
+    // TODO: Regular expression separators
+    if !separator.is_string() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            2,
+            context.name.to_string(),
+        ));
+    }
+
+    let limit = if limit.is_undefined() {
+        None
+    } else if !limit.is_number() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            3,
+            context.name.to_string(),
+        ));
+    } else if limit.as_f64() < 0.0 {
+        return Err(Error::D3020NegativeLimit(context.char_index));
+    } else {
+        Some(limit.as_f64().floor() as usize)
+    };
+
+    let string = string.as_str();
+    let separator = separator.as_str();
+
+    // An empty separator splits the string into its individual characters
+    let parts: Vec<String> = if separator.is_empty() {
+        string.chars().map(String::from).collect()
+    } else {
+        string.split(&*separator).map(String::from).collect()
+    };
+
+    let limit = limit.unwrap_or(parts.len()).min(parts.len());
+
+    let result = Value::array_with_capacity(context.arena, limit, ArrayFlags::empty());
+    for part in parts.into_iter().take(limit) {
+        result.push(Value::string(context.arena, part));
+    }
+
+    Ok(result)
+}
+
+#[signature("<s-(sf)(sf)n?:s>")]
+pub fn fn_replace<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    string: &'a Value<'a>,
+    pattern: &'a Value<'a>,
+    replacement: &'a Value<'a>,
+    limit: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if string.is_undefined() {
+        return Ok(Value::undefined());
+    }
+
+    if !string.is_string() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            1,
+            context.name.to_string(),
+        ));
+    }
+
+    // TODO: Regular expression patterns
+    if !pattern.is_string() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            2,
+            context.name.to_string(),
+        ));
+    }
+
+    // An empty pattern would match at every position without ever advancing
+    if pattern.as_str().is_empty() {
+        return Err(Error::D3010EmptyPattern(context.char_index));
+    }
+
+    if !replacement.is_string() && !replacement.is_function() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            3,
+            context.name.to_string(),
+        ));
+    }
+
+    let limit = if limit.is_undefined() {
+        None
+    } else if !limit.is_number() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            4,
+            context.name.to_string(),
+        ));
+    } else if limit.as_f64() < 0.0 {
+        return Err(Error::D3011NegativeLimit(context.char_index));
+    } else {
+        Some(limit.as_f64().floor() as usize)
+    };
+
+    let string = string.as_str();
+    let pattern = pattern.as_str();
+
+    // A function replacement is called with a match object for each match, in the same form as
+    // `$match` produces, and returns the string to substitute
+    if replacement.is_function() {
+        let mut result = String::with_capacity(string.len());
+        let mut last = 0;
+
+        for (byte_index, matched) in string
+            .match_indices(&*pattern)
+            .take(limit.unwrap_or(usize::MAX))
+        {
+            result.push_str(&string[last..byte_index]);
+
+            let match_obj = Value::object_with_capacity(context.arena, 3);
+            match_obj.insert("match", Value::string(context.arena, matched));
+            match_obj.insert(
+                "index",
+                Value::number(context.arena, string[..byte_index].chars().count()),
+            );
+            match_obj.insert("groups", Value::array(context.arena, ArrayFlags::empty()));
+
+            let substitute = context.evaluate_callback(replacement, &[match_obj])?;
+            if !substitute.is_string() {
+                return Err(Error::D3012InvalidReplacementType(context.char_index));
+            }
+            result.push_str(&substitute.as_str());
+
+            last = byte_index + matched.len();
+        }
+
+        result.push_str(&string[last..]);
+
+        return Ok(Value::string(context.arena, result));
+    }
+
+    let replacement = replacement.as_str();
+
+    let result = match limit {
+        Some(limit) => string.replacen(&*pattern, &replacement, limit),
+        None => string.replace(&*pattern, &replacement),
+    };
+
+    Ok(Value::string(context.arena, result))
+}
+
+#[signature("<a<s>s?:s>")]
+pub fn fn_join<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    strings: &'a Value<'a>,
+    separator: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if strings.is_undefined() {
+        return Ok(Value::undefined());
+    }
+
+    let strings = Value::wrap_in_array_if_needed(context.arena, strings, ArrayFlags::empty());
+
+    if !strings.members().all(|s| s.is_string()) {
+        return Err(Error::T0412ArgumentMustBeArrayOfType(
+            context.char_index,
+            1,
+            context.name.to_string(),
+            "string".to_string(),
+        ));
+    }
+
+    let separator = if separator.is_undefined() {
+        String::from("")
+    } else if separator.is_string() {
+        separator.as_str().to_string()
+    } else {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            2,
+            context.name.to_string(),
+        ));
+    };
+
+    let result = strings
+        .members()
+        .map(|s| s.as_str())
+        .collect::<Vec<_>>()
+        .join(&separator);
+
+    Ok(Value::string(context.arena, result))
+}
+
+#[signature("<(nsb)-:n>")]
+pub fn fn_number<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    arg: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    match arg {
+        Value::Undefined => Ok(Value::undefined()),
+
+        // Numbers are returned as-is, there's no need to format and parse them again
+        Value::Number(..) => Ok(arg),
+
+        Value::Bool(b) => Ok(Value::number(context.arena, if *b { 1 } else { 0 })),
+
+        Value::String(ref s) => {
+            let number = if is_numeric_string(s) {
+                s.parse::<f64>().ok().filter(|n| n.is_finite())
+            } else {
+                None
+            };
+
+            match number {
+                Some(number) => Ok(Value::number(context.arena, number)),
+                None => Err(Error::D3030NonNumericCast(
+                    context.char_index,
+                    s.to_string(),
+                )),
+            }
+        }
+
+        _ => Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            1,
+            context.name.to_string(),
+        )),
+    }
+}
+
+/// Checks that a string is a decimal number in the form `$number` accepts, which is a JSON number
+/// except that leading zeroes are allowed. Whitespace isn't trimmed, so `" 42 "` isn't numeric.
+fn is_numeric_string(s: &str) -> bool {
+    fn skip_digits(bytes: &[u8]) -> usize {
+        bytes.iter().take_while(|b| b.is_ascii_digit()).count()
+    }
+
+    let bytes = s.as_bytes();
+    let mut i = if bytes.first() == Some(&b'-') { 1 } else { 0 };
+
+    let digits = skip_digits(&bytes[i..]);
+    if digits == 0 {
+        return false;
+    }
+    i += digits;
+
+    if bytes.get(i) == Some(&b'.') {
+        let digits = skip_digits(&bytes[i + 1..]);
+        if digits == 0 {
+            return false;
+        }
+        i += 1 + digits;
+    }
+
+    if matches!(bytes.get(i), Some(b'e') | Some(b'E')) {
+        i += 1;
+        if matches!(bytes.get(i), Some(b'-') | Some(b'+')) {
+            i += 1;
         }
+        let digits = skip_digits(&bytes[i..]);
+        if digits == 0 {
+            return false;
+        }
+        i += digits;
     }
+
+    i == bytes.len()
 }
 
 #[signature("<n-:n>")]
@@ -440,6 +1518,221 @@ pub fn fn_ceil<'a, 'e>(
     }
 }
 
+#[signature("<n-n:n>")]
+pub fn fn_power<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    base: &'a Value<'a>,
+    exponent: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if base.is_undefined() {
+        return Ok(Value::undefined());
+    }
+
+    if !base.is_number() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            1,
+            context.name.to_string(),
+        ));
+    }
+
+    if !exponent.is_number() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            2,
+            context.name.to_string(),
+        ));
+    }
+
+    let base = base.as_f64();
+    let exponent = exponent.as_f64();
+    let result = base.powf(exponent);
+
+    if !result.is_finite() {
+        return Err(Error::D3061PowUnrepresentable(
+            context.char_index,
+            base,
+            exponent,
+        ));
+    }
+
+    Ok(Value::number(context.arena, result))
+}
+
+#[signature("<n-n?:s>")]
+pub fn fn_format_base<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    value: &'a Value<'a>,
+    radix: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if value.is_undefined() {
+        return Ok(Value::undefined());
+    }
+
+    if !value.is_number() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            1,
+            context.name.to_string(),
+        ));
+    }
+
+    let radix = if radix.is_undefined() {
+        10.0
+    } else if radix.is_number() {
+        radix.as_f64()
+    } else {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            2,
+            context.name.to_string(),
+        ));
+    };
+
+    // Unlike the value, a radix with a fractional part isn't rounded, as there's no sensible
+    // base to round it to
+    if radix.fract() != 0.0 || !(2.0..=36.0).contains(&radix) {
+        return Err(Error::D3100RadixOutOfRange(context.char_index, radix));
+    }
+
+    let value = round_half_even(value.as_f64());
+    let radix = radix as u32;
+
+    let mut digits = Vec::new();
+    let mut remaining = value.abs();
+    loop {
+        let digit = remaining % radix as f64;
+        digits.push(std::char::from_digit(digit as u32, radix).unwrap());
+        remaining = (remaining - digit) / radix as f64;
+        if remaining < 1.0 {
+            break;
+        }
+    }
+    if value < 0.0 {
+        digits.push('-');
+    }
+
+    Ok(Value::string(
+        context.arena,
+        digits.iter().rev().collect::<String>(),
+    ))
+}
+
+#[signature("<n-s:s>")]
+pub fn fn_format_integer<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    value: &'a Value<'a>,
+    picture: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if value.is_undefined() {
+        return Ok(Value::undefined());
+    }
+
+    if !value.is_number() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            1,
+            context.name.to_string(),
+        ));
+    }
+
+    if !picture.is_string() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            2,
+            context.name.to_string(),
+        ));
+    }
+
+    let formatted = format_integer(context.char_index, value.as_f64(), &picture.as_str())?;
+
+    Ok(Value::string(context.arena, formatted))
+}
+
+#[signature("<n-so?:s>")]
+pub fn fn_format_number<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    value: &'a Value<'a>,
+    picture: &'a Value<'a>,
+    options: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if value.is_undefined() {
+        return Ok(Value::undefined());
+    }
+
+    if !value.is_number() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            1,
+            context.name.to_string(),
+        ));
+    }
+
+    if !picture.is_string() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            2,
+            context.name.to_string(),
+        ));
+    }
+
+    let mut format = DecimalFormat::default();
+
+    if !options.is_undefined() {
+        if !options.is_object() {
+            return Err(Error::T0410ArgumentNotValid(
+                context.char_index,
+                3,
+                context.name.to_string(),
+            ));
+        }
+
+        // Options that aren't strings, or aren't XPath decimal format properties, are ignored.
+        // Only percent and per-mille can be longer than a single character.
+        for (key, option) in options.entries() {
+            if !option.is_string() {
+                continue;
+            }
+            let option = option.as_str();
+            let c = match option.chars().next() {
+                Some(c) => c,
+                None => continue,
+            };
+            match key.as_str() {
+                "decimal-separator" => format.decimal_separator = c,
+                "grouping-separator" => format.grouping_separator = c,
+                "exponent-separator" => format.exponent_separator = c,
+                "minus-sign" => format.minus_sign = c,
+                "percent" => format.percent = option.to_string(),
+                "per-mille" => format.per_mille = option.to_string(),
+                "zero-digit" => format.zero_digit = c,
+                "digit" => format.digit = c,
+                "pattern-separator" => format.pattern_separator = c,
+                _ => {}
+            }
+        }
+    }
+
+    let formatted = format_number(
+        context.char_index,
+        value.as_f64(),
+        &picture.as_str(),
+        &format,
+    )?;
+
+    Ok(Value::string(context.arena, formatted))
+}
+
+/// Rounds to the nearest integer, with halves going to the nearest even integer the same way
+/// JSONata's `$round` does, e.g. `2.5` rounds to `2` and `3.5` rounds to `4`.
+fn round_half_even(n: f64) -> f64 {
+    if (n - n.trunc()).abs() == 0.5 {
+        2.0 * (n / 2.0).round()
+    } else {
+        n.round()
+    }
+}
+
 #[signature("<a<n>:n>")]
 pub fn fn_max<'a, 'e>(
     context: FunctionContext<'a, 'e>,
@@ -454,7 +1747,7 @@ pub fn fn_max<'a, 'e>(
         if !arg.is_number() {
             return Err(Error::T0412ArgumentMustBeArrayOfType(
                 context.char_index,
-                2,
+                1,
                 context.name.to_string(),
                 "number".to_string(),
             ));
@@ -478,7 +1771,7 @@ pub fn fn_min<'a, 'e>(
         if !arg.is_number() {
             return Err(Error::T0412ArgumentMustBeArrayOfType(
                 context.char_index,
-                2,
+                1,
                 context.name.to_string(),
                 "number".to_string(),
             ));
@@ -493,8 +1786,31 @@ pub fn fn_sum<'a, 'e>(
     context: FunctionContext<'a, 'e>,
     args: &'a Value<'a>,
 ) -> Result<&'a Value<'a>> {
+    match sum_numbers(&context, args)? {
+        Some((sum, _)) => Ok(Value::number(context.arena, sum)),
+        None => Ok(Value::undefined()),
+    }
+}
+
+#[signature("<a<n>:n>")]
+pub fn fn_average<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    args: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    match sum_numbers(&context, args)? {
+        Some((sum, count)) => Ok(Value::number(context.arena, sum / count as f64)),
+        None => Ok(Value::undefined()),
+    }
+}
+
+// Sums the numbers for $sum and $average, along with how many there were, or None when there
+// aren't any
+fn sum_numbers<'a>(
+    context: &FunctionContext<'a, '_>,
+    args: &'a Value<'a>,
+) -> Result<Option<(f64, usize)>> {
     if args.is_undefined() || (args.is_array() && args.is_empty()) {
-        return Ok(Value::undefined());
+        return Ok(None);
     }
     let args = Value::wrap_in_array_if_needed(context.arena, args, ArrayFlags::empty());
     let mut sum = 0.0;
@@ -502,12 +1818,77 @@ pub fn fn_sum<'a, 'e>(
         if !arg.is_number() {
             return Err(Error::T0412ArgumentMustBeArrayOfType(
                 context.char_index,
-                2,
+                1,
                 context.name.to_string(),
                 "number".to_string(),
             ));
         }
         sum += arg.as_f64();
     }
-    Ok(Value::number(context.arena, sum))
+
+    // The same as the arithmetic operators, a sum that can't be represented is an error
+    if sum.is_infinite() {
+        return Err(Error::D1001NumberOfOutRange(sum));
+    }
+
+    Ok(Some((sum, args.len())))
+}
+
+#[signature("<s?:x>")]
+pub fn fn_error<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    message: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    // Without an argument the context input is passed instead, which is only taken as the
+    // message when it's a string
+    let message = if message.is_string() {
+        message.as_str().to_string()
+    } else {
+        String::from("$error() function evaluated")
+    };
+
+    Err(Error::D3137Error(context.char_index, message))
+}
+
+/// Raises an error with the given message when the condition is false. The standard form
+/// returns undefined when the condition holds; when a third argument is given, it's returned
+/// instead so that the assertion can guard a value in a chain, e.g.
+/// `$positive := function($v) { $assert($v > 0, "not positive", $v) }; price ~> $positive()`.
+#[signature("<bs?x?:x>")]
+pub fn fn_assert<'a, 'e>(
+    context: FunctionContext<'a, 'e>,
+    condition: &'a Value<'a>,
+    message: &'a Value<'a>,
+    value: &'a Value<'a>,
+) -> Result<&'a Value<'a>> {
+    if !condition.is_undefined() && !condition.is_bool() {
+        return Err(Error::T0410ArgumentNotValid(
+            context.char_index,
+            1,
+            context.name.to_string(),
+        ));
+    }
+
+    if !condition.is_bool() || !condition.as_bool() {
+        let message = if message.is_string() {
+            message.as_str().to_string()
+        } else {
+            String::from("$assert() statement failed")
+        };
+
+        return Err(Error::D3141Assert(context.char_index, message));
+    }
+
+    Ok(value)
+}
+
+// TODO: Picture string and timezone arguments
+#[signature("<:s>")]
+pub fn fn_now<'a, 'e>(context: FunctionContext<'a, 'e>) -> Result<&'a Value<'a>> {
+    let timestamp = context
+        .evaluator
+        .timestamp()
+        .to_rfc3339_opts(SecondsFormat::Millis, true);
+
+    Ok(Value::string(context.arena, timestamp))
 }
@@ -340,16 +340,78 @@ impl From<f64> for Number {
             _ => {}
         }
 
-        if !float.is_sign_positive() {
-            let (mantissa, exponent) = grisu2::convert(-float);
+        let (mantissa, exponent) = shortest_parts(float.abs());
 
-            Number::from_parts(false, mantissa, exponent)
-        } else {
-            let (mantissa, exponent) = grisu2::convert(float);
+        Number::from_parts(float.is_sign_positive(), mantissa, exponent)
+    }
+}
 
-            Number::from_parts(true, mantissa, exponent)
+/// Decompose a finite, non-negative float into the shortest mantissa and exponent that round
+/// trip back to the same float, which is what JavaScript uses when converting numbers to strings.
+///
+/// Grisu2 on its own doesn't guarantee the shortest (or even the closest) digits, so this goes
+/// through the standard library's formatting instead, which does. It's written into a buffer on
+/// the stack and the digits are read straight back out, so nothing is allocated.
+fn shortest_parts(float: f64) -> (u64, i16) {
+    use std::fmt::Write;
+
+    let mut repr = StackBuffer::default();
+    write!(repr, "{:e}", float).unwrap();
+
+    let mut mantissa = 0u64;
+    let mut exponent = 0i16;
+    let mut bytes = repr.as_bytes().iter();
+
+    // Each digit after the point moves the exponent down by one, as they're read as an integer
+    let mut fraction = false;
+    for &byte in bytes.by_ref() {
+        match byte {
+            b'.' => fraction = true,
+            b'e' => break,
+            digit => {
+                mantissa = mantissa * 10 + (digit - b'0') as u64;
+                if fraction {
+                    exponent -= 1;
+                }
+            }
         }
     }
+
+    let mut negative = false;
+    let mut power = 0i16;
+    for &byte in bytes {
+        match byte {
+            b'-' => negative = true,
+            digit => power = power * 10 + (digit - b'0') as i16,
+        }
+    }
+
+    (mantissa, exponent + if negative { -power } else { power })
+}
+
+// Large enough for any f64 in exponent notation, the longest being e.g. `2.2250738585072014e-308`
+#[derive(Default)]
+struct StackBuffer {
+    buf: [u8; 32],
+    len: usize,
+}
+
+impl StackBuffer {
+    fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl fmt::Write for StackBuffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        self.buf
+            .get_mut(self.len..end)
+            .ok_or(fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
 }
 
 impl From<f32> for Number {
@@ -761,3 +761,25 @@ pub fn parse<'source, 'arena>(
 ) -> Result<&'arena Value<'arena>> {
     Parser::new(source, arena).parse()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_reject_non_finite_number_tokens() {
+        let arena = Bump::new();
+        for source in [
+            "NaN",
+            "Infinity",
+            "-Infinity",
+            r#"{"x": NaN}"#,
+            r#"{"x": Infinity}"#,
+            r#"{"x": -Infinity}"#,
+            "[1, NaN]",
+        ] {
+            let error = parse(source, &arena).unwrap_err();
+            assert_eq!(error.code(), "I0201", "{}", source);
+        }
+    }
+}
@@ -1,6 +1,7 @@
 // TODO: Fix visibility of all these modules, they're all pub for now
 pub mod ast;
 pub mod evaluator;
+pub mod format;
 pub mod frame;
 pub mod functions;
 pub mod json;
@@ -13,8 +14,10 @@ pub use jsonata_errors::{Error, Result};
 pub use value::Value;
 
 use bumpalo::Bump;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
 
-use ast::Ast;
+use ast::{Ast, AstKind, BinaryOp};
 use evaluator::Evaluator;
 use frame::Frame;
 use functions::*;
@@ -22,16 +25,30 @@ use value::ArrayFlags;
 
 pub struct JsonAta<'a> {
     ast: Ast,
+    builtins: Frame<'a>,
     frame: Frame<'a>,
     arena: Bump,
+    lenient_errors: bool,
+    clock: fn() -> DateTime<Utc>,
+    range_limit: usize,
 }
 
 impl<'a> JsonAta<'a> {
     pub fn new(expr: &str) -> Result<JsonAta<'a>> {
+        // Built-in functions live in a frame of their own, above the one used for variables
+        // assigned by the caller, so that an assigned variable can shadow a built-in function of
+        // the same name. Bindings in the expression itself shadow both in turn.
+        let builtins = Frame::new();
+        let frame = Frame::new_with_parent(&builtins);
+
         Ok(Self {
             ast: parser::parse(expr)?,
-            frame: Frame::new(),
+            builtins,
+            frame,
             arena: Bump::new(),
+            lenient_errors: false,
+            clock: Utc::now,
+            range_limit: evaluator::DEFAULT_RANGE_LIMIT,
         })
     }
 
@@ -39,6 +56,32 @@ impl<'a> JsonAta<'a> {
         &self.ast
     }
 
+    /// Enables a best-effort evaluation mode, where a subexpression that fails with a type
+    /// error (any `Txxxx` error code, e.g. a bad function argument or invoking an unknown
+    /// function) evaluates to undefined rather than failing the whole evaluation. All other
+    /// errors still abort the evaluation.
+    ///
+    /// This also makes relational comparisons (`<`, `<=`, `>` and `>=`) with `null` on either
+    /// side evaluate to undefined, the same as comparing with a missing value, so that e.g.
+    /// `items[price > 5]` skips items with a null price instead of failing with `T2010`.
+    pub fn set_lenient_errors(&mut self, lenient_errors: bool) {
+        self.lenient_errors = lenient_errors;
+    }
+
+    /// Replaces the clock that the evaluation time is read from, which defaults to the system
+    /// clock. The time is read once at the start of each evaluation and is what `$now()`
+    /// returns, so a fixed clock makes expressions that use it reproducible.
+    pub fn set_clock(&mut self, clock: fn() -> DateTime<Utc>) {
+        self.clock = clock;
+    }
+
+    /// Sets the largest sequence the range operator (`..`) is allowed to allocate, which
+    /// defaults to 10,000,000 members. A range larger than this fails with `D2014` before
+    /// anything is allocated, so lowering it bounds the memory an untrusted expression can use.
+    pub fn set_range_limit(&mut self, range_limit: usize) {
+        self.range_limit = range_limit;
+    }
+
     pub fn assign_var<'other>(&'other self, name: &str, value: &'other Value<'other>)
     where
         'other: 'a,
@@ -48,10 +91,29 @@ impl<'a> JsonAta<'a> {
 
     pub fn evaluate(&'a self, input: Option<&str>) -> Result<&'a Value<'a>> {
         let input = match input {
-            Some(input) => json::parse(input, &self.arena).unwrap(),
+            Some(input) => json::parse(input, &self.arena)?,
             None => Value::undefined(),
         };
 
+        self.evaluate_value(input)
+    }
+
+    /// Evaluates the expression the same way as [`JsonAta::evaluate`], but returns `None` when
+    /// the result is undefined rather than an undefined [`Value`].
+    pub fn evaluate_opt(&'a self, input: Option<&str>) -> Result<Option<&'a Value<'a>>> {
+        let result = self.evaluate(input)?;
+        Ok(if result.is_undefined() {
+            None
+        } else {
+            Some(result)
+        })
+    }
+
+    /// Evaluates the expression against an input that has already been parsed, e.g. with
+    /// [`json::parse`] into an arena owned by the caller. The input is borrowed rather than
+    /// copied, so a large document can be parsed once and evaluated against repeatedly without
+    /// paying to parse it again for each evaluation.
+    pub fn evaluate_value(&'a self, input: &'a Value<'a>) -> Result<&'a Value<'a>> {
         // If the input is an array, wrap it in an array so that it gets treated as a single input
         let input = if input.is_array() {
             Value::wrap_in_array(&self.arena, input, ArrayFlags::WRAPPED)
@@ -59,32 +121,652 @@ impl<'a> JsonAta<'a> {
             input
         };
 
+        self.frame.bind("$", input);
+        self.bind_builtins();
+
+        let chain_ast = parser::parse("function($f, $g) { function($x){ $g($f($x)) } }")?;
+        let mut evaluator = Evaluator::new(chain_ast, &self.arena);
+        evaluator.set_lenient_errors(self.lenient_errors);
+        evaluator.set_timestamp((self.clock)());
+        evaluator.set_range_limit(self.range_limit);
+        evaluator.evaluate(&self.ast, input, &self.frame)
+    }
+
+    /// Checks that every function the expression calls by name is defined before evaluating it,
+    /// rather than finding out when the call is reached, which may be never for some inputs.
+    ///
+    /// A function is defined if it's a built-in, has been assigned with [`JsonAta::assign_var`],
+    /// or is bound anywhere in the expression itself, e.g. `$f := function($x) { ... }` or a
+    /// lambda parameter. Fails with `S0218` listing every function that isn't.
+    pub fn check_functions(&'a self) -> Result<()> {
+        self.bind_builtins();
+
+        let mut local = HashSet::new();
+        let mut called = Vec::new();
+
+        self.ast.walk(&mut |node| match node.kind {
+            AstKind::Binary(BinaryOp::Bind, ref lhs, _) => {
+                if let AstKind::Var(ref name) = lhs.kind {
+                    local.insert(name.clone());
+                }
+            }
+            AstKind::Lambda { ref args, .. } => {
+                for arg in args {
+                    if let AstKind::Var(ref name) = arg.kind {
+                        local.insert(name.clone());
+                    }
+                }
+            }
+            AstKind::Function { ref proc, .. } => {
+                if let AstKind::Var(ref name) = proc.kind {
+                    called.push((proc.char_index, name.clone()));
+                }
+            }
+            AstKind::Binary(BinaryOp::Apply, _, ref rhs) => {
+                if let AstKind::Var(ref name) = rhs.kind {
+                    called.push((rhs.char_index, name.clone()));
+                }
+            }
+            _ => {}
+        });
+
+        let mut unknown: Vec<(usize, String)> = Vec::new();
+        for (char_index, name) in called {
+            let defined =
+                local.contains(&name) || self.frame.lookup(&name).is_some_and(|f| f.is_function());
+            if !defined && !unknown.iter().any(|(_, n)| *n == name) {
+                unknown.push((char_index, name));
+            }
+        }
+
+        match unknown.first() {
+            None => Ok(()),
+            Some((char_index, _)) => Err(Error::S0218UnknownFunctions(
+                *char_index,
+                unknown
+                    .iter()
+                    .map(|(_, name)| format!("${}", name))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )),
+        }
+    }
+
+    fn bind_builtins(&'a self) {
         macro_rules! bind {
             ($name:literal, $new:ident, $fn:ident) => {
-                self.frame.bind($name, Value::$new(&self.arena, $name, $fn));
+                self.builtins.bind(
+                    $name,
+                    Value::$new(&self.arena, $name, &*$fn::SIGNATURE, $fn),
+                );
             };
         }
 
-        self.frame.bind("$", input);
         bind!("lookup", nativefn2, fn_lookup);
+        bind!("keys", nativefn1, fn_keys);
+        bind!("spread", nativefn1, fn_spread);
+        bind!("sift", nativefn2, fn_sift);
+        bind!("each", nativefn2, fn_each);
+        bind!("merge", nativefn1, fn_merge);
         bind!("append", nativefn2, fn_append);
         bind!("boolean", nativefn1, fn_boolean);
         bind!("filter", nativefn2, fn_filter);
-        bind!("string", nativefn1, fn_string);
+        bind!("single", nativefn2, fn_single);
+        bind!("map", nativefn2, fn_map);
+        bind!("reduce", nativefn3, fn_reduce);
+        bind!("sort", nativefn2, fn_sort);
+        bind!("distinct", nativefn1, fn_distinct);
+        bind!("string", nativefn2, fn_string);
+        bind!("type", nativefn1, fn_type);
         bind!("count", nativefn1, fn_count);
         bind!("not", nativefn1, fn_not);
+        bind!("exists", nativefn1, fn_exists);
         bind!("uppercase", nativefn1, fn_uppercase);
+        bind!("trim", nativefn1, fn_trim);
         bind!("lowercase", nativefn1, fn_lowercase);
+        bind!("length", nativefn1, fn_length);
         bind!("substring", nativefn3, fn_substring);
+        bind!("substringBefore", nativefn2, fn_substring_before);
+        bind!("substringAfter", nativefn2, fn_substring_after);
+        bind!("contains", nativefn2, fn_contains);
+        bind!("pad", nativefn3, fn_pad);
+        bind!("split", nativefn3, fn_split);
+        bind!("replace", nativefn4, fn_replace);
+        bind!("join", nativefn2, fn_join);
+        bind!("number", nativefn1, fn_number);
         bind!("abs", nativefn1, fn_abs);
         bind!("max", nativefn1, fn_max);
         bind!("min", nativefn1, fn_min);
         bind!("ceil", nativefn1, fn_ceil);
         bind!("floor", nativefn1, fn_floor);
+        bind!("power", nativefn2, fn_power);
+        bind!("formatBase", nativefn2, fn_format_base);
+        bind!("formatInteger", nativefn2, fn_format_integer);
+        bind!("formatNumber", nativefn3, fn_format_number);
         bind!("sum", nativefn1, fn_sum);
+        bind!("average", nativefn1, fn_average);
+        bind!("error", nativefn1, fn_error);
+        bind!("assert", nativefn3, fn_assert);
+        bind!("now", nativefn0, fn_now);
+    }
+}
 
-        let chain_ast = parser::parse("function($f, $g) { function($x){ $g($f($x)) } }")?;
-        let evaluator = Evaluator::new(chain_ast, &self.arena);
-        evaluator.evaluate(&self.ast, input, &self.frame)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lenient_errors_disabled_by_default() {
+        let jsonata = JsonAta::new(r#"{ "a": 1 + "x", "b": 2 }"#).unwrap();
+        let result = jsonata.evaluate(None);
+        assert_eq!(result.unwrap_err().code(), "T2002");
+    }
+
+    #[test]
+    fn lenient_errors_drop_failing_branch() {
+        let mut jsonata = JsonAta::new(r#"{ "a": 1 + "x", "b": 2, "c": $bogus() }"#).unwrap();
+        jsonata.set_lenient_errors(true);
+        let result = jsonata.evaluate(None).unwrap();
+        assert!(result.is_object());
+        assert!(result["a"].is_undefined());
+        assert!(result["c"].is_undefined());
+        assert_eq!(result["b"], 2);
+    }
+
+    #[test]
+    fn lenient_errors_still_abort_on_other_errors() {
+        let mut jsonata = JsonAta::new(r#"{ "a": 1e300 * 1e300, "b": 2 }"#).unwrap();
+        jsonata.set_lenient_errors(true);
+        let result = jsonata.evaluate(None);
+        assert_eq!(result.unwrap_err().code(), "D1001");
+    }
+
+    #[test]
+    fn null_comparison_errors_by_default() {
+        let jsonata = JsonAta::new("null < 5").unwrap();
+        let result = jsonata.evaluate(None);
+        assert_eq!(result.unwrap_err().code(), "T2010");
+    }
+
+    #[test]
+    fn null_comparison_is_undefined_when_lenient() {
+        let mut jsonata = JsonAta::new("null < 5").unwrap();
+        jsonata.set_lenient_errors(true);
+        assert!(jsonata.evaluate(None).unwrap().is_undefined());
+
+        let mut jsonata = JsonAta::new("items[price > 5].name").unwrap();
+        jsonata.set_lenient_errors(true);
+        let result = jsonata
+            .evaluate(Some(
+                r#"{ "items": [
+                    { "name": "a", "price": 10 },
+                    { "name": "b", "price": null },
+                    { "name": "c", "price": 7 }
+                ] }"#,
+            ))
+            .unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(*result.get_member(0), "a");
+        assert_eq!(*result.get_member(1), "c");
+    }
+
+    #[test]
+    fn boolean_operators() {
+        let jsonata = JsonAta::new("true and false").unwrap();
+        assert_eq!(*jsonata.evaluate(None).unwrap(), false);
+
+        let jsonata = JsonAta::new("(5 > 3) or (1 > 9)").unwrap();
+        assert_eq!(*jsonata.evaluate(None).unwrap(), true);
+
+        // Unbound variables are undefined, which is falsy rather than an error
+        let jsonata = JsonAta::new("$x and $x").unwrap();
+        assert_eq!(*jsonata.evaluate(None).unwrap(), false);
+
+        let jsonata = JsonAta::new(r#"[] or "" or 0 or missing"#).unwrap();
+        assert_eq!(*jsonata.evaluate(Some("{}")).unwrap(), false);
+    }
+
+    #[test]
+    fn results_compare_structurally() {
+        let a = JsonAta::new(r#"{ "a": [1, { "b": "c" }], "d": null }"#).unwrap();
+        let b = JsonAta::new(r#"{ "d": null, "a": [1, { "b": "c" }] }"#).unwrap();
+        let c = JsonAta::new(r#"{ "a": [1, { "b": "x" }], "d": null }"#).unwrap();
+
+        let a = a.evaluate(None).unwrap();
+        let b = b.evaluate(None).unwrap();
+        let c = c.evaluate(None).unwrap();
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a["a"], b["a"]);
+        assert_ne!(a["a"], c["a"]);
+        assert_eq!(a["a"].get_member(1)["b"], "c");
+    }
+
+    #[test]
+    fn sort_comparator_error_aborts_sort() {
+        let jsonata = JsonAta::new(r#"$sort([2, 1, 3], function($a, $b) { $error("boom") })"#);
+        let error = jsonata.unwrap().evaluate(None).unwrap_err();
+        assert_eq!(error.code(), "D3137");
+        assert_eq!(error.to_string(), "D3137 @ 36: boom");
+    }
+
+    #[test]
+    fn evaluate_opt_undefined() {
+        let jsonata = JsonAta::new("a.missing").unwrap();
+        assert!(jsonata
+            .evaluate_opt(Some(r#"{ "a": { "b": 1 } }"#))
+            .unwrap()
+            .is_none());
+
+        let jsonata = JsonAta::new("a.b").unwrap();
+        let result = jsonata
+            .evaluate_opt(Some(r#"{ "a": { "b": 1 } }"#))
+            .unwrap();
+        assert_eq!(*result.unwrap(), 1);
+
+        // Null is a value, not undefined
+        let jsonata = JsonAta::new("a").unwrap();
+        let result = jsonata.evaluate_opt(Some(r#"{ "a": null }"#)).unwrap();
+        assert!(result.unwrap().is_null());
+
+        let jsonata = JsonAta::new(r#"1 + "x""#).unwrap();
+        assert_eq!(jsonata.evaluate_opt(None).unwrap_err().code(), "T2002");
+    }
+
+    #[test]
+    fn now_uses_injected_clock() {
+        use chrono::TimeZone;
+
+        fn fixed_clock() -> DateTime<Utc> {
+            Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap() + chrono::Duration::milliseconds(123)
+        }
+
+        let mut jsonata = JsonAta::new("$now()").unwrap();
+        jsonata.set_clock(fixed_clock);
+        let result = jsonata.evaluate(None).unwrap();
+        assert_eq!(*result, "2020-01-01T00:00:00.123Z");
+
+        // Every call within one evaluation sees the same time
+        let mut jsonata = JsonAta::new("$now() = $now()").unwrap();
+        jsonata.set_clock(Utc::now);
+        assert_eq!(*jsonata.evaluate(None).unwrap(), true);
+    }
+
+    #[test]
+    fn range_limit_lowered() {
+        let mut jsonata = JsonAta::new("$count([1..10])").unwrap();
+        jsonata.set_range_limit(10);
+        assert_eq!(*jsonata.evaluate(None).unwrap(), 10);
+
+        let mut jsonata = JsonAta::new("$count([1..11])").unwrap();
+        jsonata.set_range_limit(10);
+        let error = jsonata.evaluate(None).unwrap_err();
+        assert_eq!(error.code(), "D2014");
+        assert_eq!(
+            error.to_string(),
+            "D2014 @ 7: The size of the sequence allocated by the range operator (..) must not exceed 10.  Attempted to allocate 11"
+        );
+    }
+
+    #[test]
+    fn range_limit_raised() {
+        let jsonata = JsonAta::new("$count([1..10000001])").unwrap();
+        assert_eq!(jsonata.evaluate(None).unwrap_err().code(), "D2014");
+
+        let mut jsonata = JsonAta::new("$count([1..10])").unwrap();
+        jsonata.set_range_limit(5);
+        assert_eq!(jsonata.evaluate(None).unwrap_err().code(), "D2014");
+
+        let mut jsonata = JsonAta::new("$count([1..10])").unwrap();
+        jsonata.set_range_limit(20);
+        assert_eq!(*jsonata.evaluate(None).unwrap(), 10);
+    }
+
+    #[test]
+    fn convert_results_into_rust_types() {
+        let jsonata = JsonAta::new("$sum(values)").unwrap();
+        let result = jsonata.evaluate(Some(r#"{ "values": [1, 2, 3.5] }"#));
+        let n: f64 = result.unwrap().try_into().unwrap();
+        assert_eq!(n, 6.5);
+
+        let jsonata = JsonAta::new("$count(values)").unwrap();
+        let result = jsonata.evaluate(Some(r#"{ "values": [1, 2, 3] }"#));
+        let n: i64 = result.unwrap().try_into().unwrap();
+        assert_eq!(n, 3);
+
+        let jsonata = JsonAta::new(r#"$uppercase("hello")"#).unwrap();
+        let s: String = jsonata.evaluate(None).unwrap().try_into().unwrap();
+        assert_eq!(s, "HELLO");
+
+        let jsonata = JsonAta::new("1 < 2").unwrap();
+        let b: bool = jsonata.evaluate(None).unwrap().try_into().unwrap();
+        assert!(b);
+    }
+
+    #[test]
+    fn convert_result_of_wrong_type() {
+        let jsonata = JsonAta::new(r#""hello""#).unwrap();
+        let result: Result<f64> = jsonata.evaluate(None).unwrap().try_into();
+        assert_eq!(
+            result.unwrap_err(),
+            Error::I0205WrongType("number".to_string())
+        );
+
+        let jsonata = JsonAta::new("1.5").unwrap();
+        let result: Result<i64> = jsonata.evaluate(None).unwrap().try_into();
+        assert_eq!(result.unwrap_err().code(), "I0205");
+
+        let jsonata = JsonAta::new("1e300").unwrap();
+        let result: Result<i64> = jsonata.evaluate(None).unwrap().try_into();
+        assert_eq!(result.unwrap_err().code(), "I0205");
+
+        let jsonata = JsonAta::new("-1e300").unwrap();
+        let result: Result<i64> = jsonata.evaluate(None).unwrap().try_into();
+        assert_eq!(result.unwrap_err().code(), "I0205");
+
+        let jsonata = JsonAta::new("missing").unwrap();
+        let result: Result<String> = jsonata.evaluate(None).unwrap().try_into();
+        assert_eq!(result.unwrap_err().code(), "I0205");
+    }
+
+    #[test]
+    fn aggregation_errors_name_the_first_argument() {
+        for name in ["sum", "max", "min", "average"] {
+            let jsonata = JsonAta::new(&format!(r#"${}([1, "2"])"#, name)).unwrap();
+            let error = jsonata.evaluate(None).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!(
+                    "T0412 @ 0: Argument 1 of function {} must be an array of number",
+                    name
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn check_functions_rejects_unknown_function() {
+        let jsonata = JsonAta::new("$bogus()").unwrap();
+        let error = jsonata.check_functions().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "S0218 @ 0: The expression calls functions that aren't defined: $bogus"
+        );
+
+        // Calls that are never reached are still checked, and each name is listed once
+        let jsonata = JsonAta::new(r#"false ? $bogus() : ("a" ~> $nope ~> $bogus)"#).unwrap();
+        let error = jsonata.check_functions().unwrap_err();
+        assert_eq!(error.code(), "S0218");
+        assert!(error.to_string().ends_with("$bogus, $nope"));
+    }
+
+    #[test]
+    fn check_functions_accepts_defined_functions() {
+        let jsonata = JsonAta::new(r#"$uppercase("a") & $string(1)"#).unwrap();
+        assert!(jsonata.check_functions().is_ok());
+
+        let jsonata = JsonAta::new(
+            "($double := function($x) { $x * 2 }; $map([1], function($v) { $double($v) }))",
+        )
+        .unwrap();
+        assert!(jsonata.check_functions().is_ok());
+
+        let jsonata = JsonAta::new("function($f) { $f(1) }").unwrap();
+        assert!(jsonata.check_functions().is_ok());
+
+        let jsonata = JsonAta::new("$custom(1)").unwrap();
+        jsonata.assign_var(
+            "custom",
+            Value::nativefn1(&jsonata.arena, "custom", &[], fn_count),
+        );
+        assert!(jsonata.check_functions().is_ok());
+
+        // A variable that isn't a function can't be called
+        let jsonata = JsonAta::new("$custom(1)").unwrap();
+        jsonata.assign_var("custom", Value::number(&jsonata.arena, 1));
+        assert_eq!(jsonata.check_functions().unwrap_err().code(), "S0218");
+    }
+
+    #[test]
+    fn error_position() {
+        let error = JsonAta::new("(1 + ]").err().unwrap();
+        assert_eq!(error.code(), "S0211");
+        assert_eq!(error.position(), Some(5));
+
+        let jsonata = JsonAta::new(r#"1 + "x""#).unwrap();
+        assert_eq!(jsonata.evaluate(None).unwrap_err().position(), Some(2));
+
+        assert_eq!(Error::D1001NumberOfOutRange(f64::INFINITY).position(), None);
+        assert_eq!(Error::I0202UnexpectedEndOfJson.position(), None);
+    }
+
+    #[test]
+    fn non_string_object_key_is_an_error() {
+        let jsonata = JsonAta::new(r#"{ 1: "x" }"#).unwrap();
+        let error = jsonata.evaluate(None).unwrap_err();
+        assert_eq!(error.code(), "T1003");
+        assert_eq!(
+            error.to_string(),
+            "T1003 @ 0: Key in object structure must evaluate to a string; got: 1"
+        );
+
+        // The position is that of the object constructor with the offending key
+        let jsonata = JsonAta::new(r#"{ "a": { true: "x" } }"#).unwrap();
+        let error = jsonata.evaluate(None).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "T1003 @ 7: Key in object structure must evaluate to a string; got: true"
+        );
+    }
+
+    #[test]
+    fn assigned_function_shadows_builtin() {
+        let jsonata = JsonAta::new("$sum([5, 6])").unwrap();
+        jsonata.assign_var(
+            "sum",
+            Value::nativefn1(&jsonata.arena, "sum", &[], fn_count),
+        );
+        assert_eq!(*jsonata.evaluate(None).unwrap(), 2);
+
+        // Evaluating again mustn't replace the assigned function with the builtin
+        assert_eq!(*jsonata.evaluate(None).unwrap(), 2);
+
+        let jsonata = JsonAta::new("$sum([5, 6])").unwrap();
+        assert_eq!(*jsonata.evaluate(None).unwrap(), 11);
+    }
+
+    #[test]
+    fn invalid_input_is_an_error() {
+        let jsonata = JsonAta::new("x").unwrap();
+        for input in [r#"{"x": NaN}"#, r#"{"x": Infinity}"#, r#"{"x": 1"#] {
+            assert!(jsonata.evaluate(Some(input)).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn object_entries_in_insertion_order() {
+        let jsonata = JsonAta::new(r#"{ "z": 1, "a": [2], "m": { "n": null } }"#).unwrap();
+        let result = jsonata.evaluate(None).unwrap();
+
+        let keys: Vec<&str> = result.as_object_entries().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["z", "a", "m"]);
+
+        let (_, a) = result.as_object_entries().nth(1).unwrap();
+        assert_eq!(*a.get_member(0), 2);
+
+        let jsonata = JsonAta::new("[1, 2]").unwrap();
+        let result = jsonata.evaluate(None).unwrap();
+        assert_eq!(result.as_object_entries().count(), 0);
+        assert_eq!(Value::undefined().as_object_entries().count(), 0);
+    }
+
+    #[test]
+    fn object_functions_agree_on_key_order() {
+        let jsonata = JsonAta::new(
+            r#"(
+                $object := { "b": 1, "c": 2, "a": 3 };
+                {
+                    "keys": $keys($object),
+                    "spread": $spread($object).$keys(),
+                    "each": $each($object, function($v, $k) { $k }),
+                    "sift": $keys($sift($object, function($v) { true }))
+                }
+            )"#,
+        )
+        .unwrap();
+        let result = jsonata.evaluate(None).unwrap();
+
+        for (function, keys) in result.as_object_entries() {
+            let keys: Vec<String> = keys.members().map(|key| key.as_str().to_string()).collect();
+            assert_eq!(keys, ["b", "c", "a"], "${} key order", function);
+        }
+        assert_eq!(result.as_object_entries().count(), 4);
+    }
+
+    #[test]
+    fn evaluate_borrowed_input() {
+        let arena = Bump::new();
+        let document = json::parse(r#"{ "a": [1, 2, 3], "b": { "c": "d" } }"#, &arena).unwrap();
+
+        let sum = JsonAta::new("$sum(a)").unwrap();
+        let field = JsonAta::new("b.c").unwrap();
+
+        assert_eq!(*sum.evaluate_value(document).unwrap(), 6);
+        assert_eq!(*field.evaluate_value(document).unwrap(), "d");
+        assert_eq!(*sum.evaluate_value(document).unwrap(), 6);
+    }
+
+    #[test]
+    fn merge_skips_undefined_members() {
+        let arena = Bump::new();
+        let objects = Value::array(&arena, ArrayFlags::empty());
+        objects.push(json::parse(r#"{ "a": 1 }"#, &arena).unwrap());
+        objects.push(Value::undefined());
+        objects.push(json::parse(r#"{ "b": 2 }"#, &arena).unwrap());
+
+        let jsonata = JsonAta::new("$merge($objects)").unwrap();
+        jsonata.assign_var("objects", objects);
+
+        let result = jsonata.evaluate(None).unwrap();
+        let expected = JsonAta::new(r#"{ "a": 1, "b": 2 }"#).unwrap();
+        assert_eq!(result, expected.evaluate(None).unwrap());
+    }
+
+    #[test]
+    fn distinct_keeps_first_occurrence() {
+        let arena = Bump::new();
+        let document = json::parse(r#"{ "a": [{ "b": 1 }, { "c": 2 }, { "b": 1 }] }"#, &arena);
+        let document = document.unwrap();
+
+        let jsonata = JsonAta::new("$distinct(a)").unwrap();
+        let result = jsonata.evaluate_value(document).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert!(std::ptr::eq(
+            result.get_member(0),
+            document["a"].get_member(0)
+        ));
+        assert!(std::ptr::eq(
+            result.get_member(1),
+            document["a"].get_member(1)
+        ));
+    }
+
+    fn split_join(s: &str, sep: &str) -> String {
+        let expr = format!(r#"$join($split("{}", "{}"), "{}")"#, s, sep, sep);
+        let jsonata = JsonAta::new(&expr).unwrap();
+        let result = jsonata.evaluate(None).unwrap();
+        result.as_str().to_string()
+    }
+
+    #[test]
+    fn split_join_round_trip_edge_cases() {
+        for (s, sep) in [
+            ("", ","),
+            ("a,b,", ","),
+            (",a,b", ","),
+            ("a,,b", ","),
+            (",,,", ","),
+            ("abc", ","),
+            ("a::b::", "::"),
+            ("héllo wörld 😀 ", " "),
+            ("😀x😀", "😀"),
+        ] {
+            assert_eq!(split_join(s, sep), s);
+        }
+    }
+
+    #[test]
+    fn split_join_round_trip_generated() {
+        const ALPHABET: [&str; 8] = ["a", "b", ",", " ", "é", "😀", "::", "ß"];
+        const SEPARATORS: [&str; 5] = [",", " ", "::", "😀", "a,"];
+
+        // Small linear congruential generator so the generated cases are deterministic
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move |bound: usize| {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            ((seed >> 33) as usize) % bound
+        };
+
+        for _ in 0..200 {
+            let len = next(16);
+            let s: String = (0..len).map(|_| ALPHABET[next(ALPHABET.len())]).collect();
+            let sep = SEPARATORS[next(SEPARATORS.len())];
+            assert_eq!(split_join(&s, sep), s, "separator {:?}", sep);
+        }
+    }
+
+    static COMPARISONS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    fn counting_comparator<'a>(
+        context: FunctionContext<'a, '_>,
+        a: &'a Value<'a>,
+        b: &'a Value<'a>,
+    ) -> Result<&'a Value<'a>> {
+        COMPARISONS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        Ok(Value::bool(context.arena, a.as_f64() > b.as_f64()))
+    }
+
+    #[test]
+    fn sort_comparator_calls_are_n_log_n() {
+        const N: usize = 2000;
+
+        // Plenty of duplicates, so equal members are compared against each other as well
+        let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+        let input: Vec<String> = (0..N)
+            .map(|_| {
+                seed = seed
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                ((seed >> 33) % 500).to_string()
+            })
+            .collect();
+        let input = format!("[{}]", input.join(","));
+
+        let jsonata = JsonAta::new("$sort($, $compare)").unwrap();
+        jsonata.assign_var(
+            "compare",
+            Value::nativefn2(&jsonata.arena, "compare", &[], counting_comparator),
+        );
+
+        COMPARISONS.store(0, std::sync::atomic::Ordering::Relaxed);
+        let result = jsonata.evaluate(Some(&input)).unwrap();
+        let comparisons = COMPARISONS.load(std::sync::atomic::Ordering::Relaxed);
+
+        assert_eq!(result.len(), N);
+        for i in 1..N {
+            assert!(result.get_member(i - 1).as_f64() <= result.get_member(i).as_f64());
+        }
+
+        let log2_n = (N as f64).log2().ceil() as usize;
+        assert!(
+            comparisons <= N * log2_n,
+            "{} comparator calls sorting {} members",
+            comparisons,
+            N
+        );
     }
 }
@@ -68,6 +68,12 @@ impl<'a> Parser<'a> {
 
 pub fn parse(source: &str) -> Result<Ast> {
     let mut parser = Parser::new(source)?;
+
+    // An expression with nothing but whitespace and comments in it has nothing to evaluate
+    if matches!(parser.token().kind, TokenKind::End) {
+        return Err(Error::S0207UnexpectedEndOfExpression(0));
+    }
+
     let ast = parser.expression(0)?;
     if !matches!(parser.token().kind, TokenKind::End) {
         return Err(Error::S0201SyntaxError(
@@ -268,4 +274,53 @@ mod tests {
     fn parser_tests(source: &str) -> Result<Ast> {
         parse(source)
     }
+
+    #[test_case("Address.City" ; "path")]
+    #[test_case("Other.`Over 18 ?`" ; "backtick name")]
+    #[test_case("Phone[type='mobile'][0].number" ; "predicates")]
+    #[test_case("a - (b - c) * -(d + e)" ; "precedence")]
+    #[test_case("$x := $y := cond ? (a ? b) : c ? d : e" ; "bind and ternaries")]
+    #[test_case("Account.Order.Product^(>Price, Quantity).Name" ; "sort")]
+    #[test_case("Phone{type: number[]}" ; "group by")]
+    #[test_case("[1..5, \"a\\\"b\\n\"][0]" ; "array constructor")]
+    #[test_case("payload ~> |Account.Order|{'Total': $sum(Price)}, ['Price']|" ; "transform")]
+    #[test_case(
+        r#"
+        (
+          $factorial := function($x){ $x <= 1 ? 1 : $x * $factorial($x - 1) };
+          $first5 := $substring(?, 0, 5);
+          Account.Order.{
+            'name': $first5(`Product Name`) & " " & $string($factorial(3)),
+            'skus': Product[Price > 10 and Quantity in [1, 2]].SKU[]
+          }
+        )
+    "# ; "complex expression"
+    )]
+    fn to_expression_string_round_trips(source: &str) {
+        let printed = parse(source).unwrap().to_expression_string();
+        let reprinted = parse(&printed).unwrap().to_expression_string();
+        assert_eq!(printed, reprinted);
+    }
+
+    #[test]
+    fn to_expression_string_is_canonical() {
+        let ast = parse("Account.Order[0].Product{ 'Price': Price*(1+tax) }").unwrap();
+        assert_eq!(
+            ast.to_expression_string(),
+            r#"Account.Order[0].Product{"Price": Price * (1 + tax)}"#
+        );
+
+        let ast = parse("x + a^(>y)").unwrap();
+        assert_eq!(ast.to_expression_string(), "(x + a)^(>y)");
+    }
+
+    #[test_case("" ; "empty")]
+    #[test_case("   " ; "whitespace only")]
+    #[test_case("\n\t \n" ; "newlines and tabs only")]
+    #[test_case("/* comment */" ; "comment only")]
+    fn empty_expression(source: &str) {
+        let error = parse(source).unwrap_err();
+        assert_eq!(error.code(), "S0207");
+        assert_eq!(error.to_string(), "S0207 @ 0: Unexpected end of expression");
+    }
 }
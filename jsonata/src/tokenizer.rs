@@ -593,14 +593,14 @@ impl<'a> Tokenizer<'a> {
                             "true" => Bool(true),
                             "false" => Bool(false),
                             "null" => Null,
-                            "function" => {
+                            name @ ("function" | "λ") => {
                                 // This is one of those times where JSONata's syntax let's us down.
                                 // Function signatures come directly after the right parentheses in a
                                 // lambda definition, i.e. `function($x)<s>{$x}`. As we have just seen
-                                // a bare `function` we flag the state that we could possibly see a
-                                // a signature.
+                                // a bare `function` (or its shorthand `λ`) we flag the state that we
+                                // could possibly see a signature.
                                 self.expect_signature = true;
-                                Name("function".to_string())
+                                Name(name.to_string())
                             }
                             _ => Name(String::from(
                                 &self.input[self.start_byte_index..self.byte_index],
@@ -831,6 +831,59 @@ impl<'a> Tokenizer<'a> {
     }
 }
 
+/// Tokenizes an expression without parsing it, e.g. for syntax highlighting.
+///
+/// Whitespace and comments are skipped, and the final `End` token isn't yielded. Tokenizing
+/// stops at the first error, which is yielded as the last item, so incomplete input such as an
+/// unterminated string ends the iterator with an `Err` rather than panicking.
+pub fn tokenize(expr: &str) -> impl Iterator<Item = Result<Token>> + '_ {
+    let mut tokenizer = Tokenizer::new(expr);
+    let mut done = false;
+
+    std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+
+        match tokenizer.next_token() {
+            Ok(Token {
+                kind: TokenKind::End,
+                ..
+            }) => {
+                done = true;
+                None
+            }
+            Ok(token) => Some(Ok(token)),
+            Err(err) => {
+                done = true;
+                Some(Err(err))
+            }
+        }
+    })
+}
+
+/// Converts a char index, as reported in errors, into a 1-based line and column in the input.
+/// `\n`, `\r\n` and a lone `\r` all end a line, so positions are reported the same way whatever
+/// line endings the expression was written with.
+pub fn line_and_column(input: &str, char_index: usize) -> (usize, usize) {
+    let mut line = 1;
+    let mut column = 1;
+    let mut chars = input.chars().take(char_index).peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\r' | '\n' => {
+                line += 1;
+                column = 1;
+            }
+            _ => column += 1,
+        }
+    }
+
+    (line, column)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1029,4 +1082,85 @@ mod tests {
         ));
         assert!(matches!(t.next_token().unwrap().kind, TokenKind::End));
     }
+
+    #[test]
+    fn positions_across_line_endings() {
+        for newline in ["\n", "\r\n", "\r"] {
+            let input = format!("(\r\n  $a := 1; /* comment */{}  $a + \"oops", newline);
+
+            let mut t = Tokenizer::new(&input);
+            let error = loop {
+                match t.next_token() {
+                    Ok(token) => assert!(!matches!(token.kind, TokenKind::End)),
+                    Err(error) => break error,
+                }
+            };
+
+            let char_index = match error {
+                Error::S0101UnterminatedStringLiteral(char_index) => char_index,
+                _ => panic!("unexpected error {}", error),
+            };
+            assert_eq!(line_and_column(&input, char_index), (3, 8), "{:?}", newline);
+        }
+    }
+
+    #[test]
+    fn line_and_column_of_token() {
+        let input = "a\r\nb\rc\nd";
+        let mut t = Tokenizer::new(input);
+        let positions: Vec<_> = std::iter::from_fn(|| match t.next_token().unwrap() {
+            Token {
+                kind: TokenKind::End,
+                ..
+            } => None,
+            token => Some(line_and_column(input, token.char_index)),
+        })
+        .collect();
+        assert_eq!(positions, [(1, 1), (2, 1), (3, 1), (4, 1)]);
+    }
+
+    #[test]
+    fn lambda_keyword() {
+        let mut t = Tokenizer::new("λ($x)<n>{$x}");
+        assert!(matches!(t.next_token().unwrap().kind, TokenKind::Name(s) if s == "λ"));
+        assert!(matches!(t.next_token().unwrap().kind, TokenKind::LeftParen));
+        assert!(matches!(t.next_token().unwrap().kind, TokenKind::Var(s) if s == "x"));
+        assert!(matches!(
+            t.next_token().unwrap().kind,
+            TokenKind::RightParen
+        ));
+        assert!(matches!(t.next_token().unwrap().kind, TokenKind::Signature(s) if s == "<n>"));
+        assert!(matches!(t.next_token().unwrap().kind, TokenKind::LeftBrace));
+    }
+
+    #[test]
+    fn tokenize_expression() {
+        let tokens: Vec<_> = tokenize("a.b + $c").map(|t| t.unwrap()).collect();
+        let kinds: Vec<_> = tokens.iter().map(|t| t.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            [
+                TokenKind::Name("a".to_string()),
+                TokenKind::Period,
+                TokenKind::Name("b".to_string()),
+                TokenKind::Plus,
+                TokenKind::Var("c".to_string()),
+            ]
+        );
+        let positions: Vec<_> = tokens.iter().map(|t| (t.char_index, t.len)).collect();
+        assert_eq!(positions, [(0, 1), (1, 1), (2, 1), (4, 1), (6, 2)]);
+    }
+
+    #[test]
+    fn tokenize_incomplete_input() {
+        for expr in ["a + \"unterminated", "a /* comment", "`name", "1e"] {
+            let mut tokens: Vec<_> = tokenize(expr).collect();
+            assert!(
+                matches!(tokens.pop(), Some(Err(_))),
+                "expected an error for {}",
+                expr
+            );
+            assert!(tokens.iter().all(|t| t.is_ok()), "{}", expr);
+        }
+    }
 }
@@ -4,14 +4,15 @@ use std::ops::Index;
 use bitflags::bitflags;
 use bumpalo::boxed::Box;
 use bumpalo::Bump;
-use hashbrown::HashMap;
+use indexmap::IndexMap;
+use jsonata_signatures::{Arg, Flags};
 
 use crate::ast::{Ast, AstKind};
 use crate::frame::Frame;
 use crate::functions::FunctionContext;
 use crate::json::codegen::{DumpGenerator, Generator, PrettyGenerator};
 use crate::json::Number;
-use crate::Result;
+use crate::{Error, Result};
 
 bitflags! {
     pub struct ArrayFlags: u8 {
@@ -37,28 +38,46 @@ pub enum Value<'a> {
     Bool(bool),
     String(String),
     Array(Box<'a, Vec<&'a Value<'a>>>, ArrayFlags),
-    Object(Box<'a, HashMap<String, &'a Value<'a>>>),
+    Object(Box<'a, IndexMap<String, &'a Value<'a>>>),
     Lambda {
         ast: Ast,
         input: &'a Value<'a>,
         frame: Frame<'a>,
     },
-    NativeFn0(String, fn(FunctionContext<'a, '_>) -> Result<&'a Value<'a>>),
+    NativeFn0(
+        String,
+        &'static [Arg],
+        fn(FunctionContext<'a, '_>) -> Result<&'a Value<'a>>,
+    ),
     NativeFn1(
         String,
+        &'static [Arg],
         fn(FunctionContext<'a, '_>, &'a Value<'a>) -> Result<&'a Value<'a>>,
     ),
     NativeFn2(
         String,
+        &'static [Arg],
         fn(FunctionContext<'a, '_>, &'a Value<'a>, &'a Value<'a>) -> Result<&'a Value<'a>>,
     ),
     NativeFn3(
         String,
+        &'static [Arg],
+        fn(
+            FunctionContext<'a, '_>,
+            &'a Value<'a>,
+            &'a Value<'a>,
+            &'a Value<'a>,
+        ) -> Result<&'a Value<'a>>,
+    ),
+    NativeFn4(
+        String,
+        &'static [Arg],
         fn(
             FunctionContext<'a, '_>,
             &'a Value<'a>,
             &'a Value<'a>,
             &'a Value<'a>,
+            &'a Value<'a>,
         ) -> Result<&'a Value<'a>>,
     ),
 }
@@ -99,12 +118,12 @@ impl<'a> Value<'a> {
     }
 
     pub fn object(arena: &Bump) -> &mut Value {
-        arena.alloc(Value::Object(Box::new_in(HashMap::new(), arena)))
+        arena.alloc(Value::Object(Box::new_in(IndexMap::new(), arena)))
     }
 
     pub fn object_with_capacity(arena: &Bump, capacity: usize) -> &mut Value {
         arena.alloc(Value::Object(Box::new_in(
-            HashMap::with_capacity(capacity),
+            IndexMap::with_capacity(capacity),
             arena,
         )))
     }
@@ -125,38 +144,57 @@ impl<'a> Value<'a> {
     pub fn nativefn0(
         arena: &'a Bump,
         name: &str,
-        func: fn(FunctionContext) -> Result<&'a Value<'a>>,
+        signature: &'static [Arg],
+        func: fn(FunctionContext<'a, '_>) -> Result<&'a Value<'a>>,
     ) -> &'a mut Value<'a> {
-        arena.alloc(Value::NativeFn0(name.to_string(), func))
+        arena.alloc(Value::NativeFn0(name.to_string(), signature, func))
     }
 
     pub fn nativefn1(
         arena: &'a Bump,
         name: &str,
+        signature: &'static [Arg],
         func: fn(FunctionContext<'a, '_>, &'a Value<'a>) -> Result<&'a Value<'a>>,
     ) -> &'a mut Value<'a> {
-        arena.alloc(Value::NativeFn1(name.to_string(), func))
+        arena.alloc(Value::NativeFn1(name.to_string(), signature, func))
     }
 
     pub fn nativefn2(
         arena: &'a Bump,
         name: &str,
+        signature: &'static [Arg],
         func: fn(FunctionContext<'a, '_>, &'a Value<'a>, &'a Value<'a>) -> Result<&'a Value<'a>>,
     ) -> &'a mut Value<'a> {
-        arena.alloc(Value::NativeFn2(name.to_string(), func))
+        arena.alloc(Value::NativeFn2(name.to_string(), signature, func))
     }
 
     pub fn nativefn3(
         arena: &'a Bump,
         name: &str,
+        signature: &'static [Arg],
+        func: fn(
+            FunctionContext<'a, '_>,
+            &'a Value<'a>,
+            &'a Value<'a>,
+            &'a Value<'a>,
+        ) -> Result<&'a Value<'a>>,
+    ) -> &'a mut Value<'a> {
+        arena.alloc(Value::NativeFn3(name.to_string(), signature, func))
+    }
+
+    pub fn nativefn4(
+        arena: &'a Bump,
+        name: &str,
+        signature: &'static [Arg],
         func: fn(
             FunctionContext<'a, '_>,
             &'a Value<'a>,
             &'a Value<'a>,
             &'a Value<'a>,
+            &'a Value<'a>,
         ) -> Result<&'a Value<'a>>,
     ) -> &'a mut Value<'a> {
-        arena.alloc(Value::NativeFn3(name.to_string(), func))
+        arena.alloc(Value::NativeFn4(name.to_string(), signature, func))
     }
 
     pub fn is_undefined(&self) -> bool {
@@ -216,6 +254,7 @@ impl<'a> Value<'a> {
                 | Value::NativeFn1(..)
                 | Value::NativeFn2(..)
                 | Value::NativeFn3(..)
+                | Value::NativeFn4(..)
         )
     }
 
@@ -243,7 +282,8 @@ impl<'a> Value<'a> {
             | Value::NativeFn0(..)
             | Value::NativeFn1(..)
             | Value::NativeFn2(..)
-            | Value::NativeFn3(..) => false,
+            | Value::NativeFn3(..)
+            | Value::NativeFn4(..) => false,
         }
     }
 
@@ -264,13 +304,27 @@ impl<'a> Value<'a> {
         }
     }
 
-    pub fn entries(&self) -> hashbrown::hash_map::Iter<'_, String, &'a Value> {
+    pub fn entries(&self) -> indexmap::map::Iter<'_, String, &'a Value> {
         match *self {
             Value::Object(ref map) => map.iter(),
             _ => panic!("Not an object"),
         }
     }
 
+    /// Iterates over the key/value pairs of an object in insertion order. Unlike
+    /// [`Value::entries`], any other kind of value yields no entries rather than panicking.
+    pub fn as_object_entries(&self) -> impl Iterator<Item = (&str, &'a Value<'a>)> + '_ {
+        let entries = match *self {
+            Value::Object(ref map) => Some(map.iter()),
+            _ => None,
+        };
+
+        entries
+            .into_iter()
+            .flatten()
+            .map(|(key, value)| (key.as_str(), *value))
+    }
+
     pub fn arity(&self) -> usize {
         match *self {
             Value::Lambda { ref ast, .. } => {
@@ -280,10 +334,11 @@ impl<'a> Value<'a> {
                     0
                 }
             }
-            Value::NativeFn0(..) => 0,
-            Value::NativeFn1(..) => 1,
-            Value::NativeFn2(..) => 2,
-            Value::NativeFn3(..) => 3,
+            Value::NativeFn0(_, signature, _) => native_arity(signature, 0),
+            Value::NativeFn1(_, signature, _) => native_arity(signature, 1),
+            Value::NativeFn2(_, signature, _) => native_arity(signature, 2),
+            Value::NativeFn3(_, signature, _) => native_arity(signature, 3),
+            Value::NativeFn4(_, signature, _) => native_arity(signature, 4),
             _ => panic!("Not a function"),
         }
     }
@@ -340,7 +395,8 @@ impl<'a> Value<'a> {
     pub fn is_empty(&self) -> bool {
         match *self {
             Value::Array(ref array, _) => array.is_empty(),
-            _ => panic!("Not an array"),
+            Value::Object(ref map) => map.is_empty(),
+            _ => panic!("Not an array or object"),
         }
     }
 
@@ -430,6 +486,23 @@ impl<'a> Value<'a> {
     }
 }
 
+// Optional parameters aren't counted, the same as in JSONata, so that e.g. `$map(values, $string)`
+// doesn't pass the index as `$string`'s prettify argument. Native functions without a signature
+// take all of their parameters.
+fn native_arity(signature: &[Arg], params: usize) -> usize {
+    if signature.is_empty() {
+        params
+    } else {
+        signature
+            .iter()
+            .filter(|arg| !arg.flags.contains(Flags::OPTIONAL))
+            .count()
+    }
+}
+
+/// Values compare structurally, so results from separate evaluations can be compared directly.
+/// Arrays compare member by member regardless of their flags, objects compare their entries
+/// regardless of key order, and functions never compare equal.
 impl<'a> PartialEq<Value<'a>> for Value<'a> {
     fn eq(&self, other: &Value<'a>) -> bool {
         match (self, other) {
@@ -508,6 +581,72 @@ impl PartialEq<String> for Value<'_> {
     }
 }
 
+/// Results convert into the equivalent Rust types with `try_into()`, e.g.
+/// `let n: f64 = result.try_into()?`, failing with `I0205` when the value is of another type.
+/// Converting to `i64` also fails for a number with a fractional part or outside its range,
+/// rather than truncating or saturating it.
+impl TryFrom<&Value<'_>> for f64 {
+    type Error = Error;
+
+    fn try_from(value: &Value<'_>) -> Result<Self> {
+        match *value {
+            Value::Number(n) => Ok(f64::from(n)),
+            _ => Err(Error::I0205WrongType("number".to_string())),
+        }
+    }
+}
+
+impl TryFrom<&Value<'_>> for i64 {
+    type Error = Error;
+
+    fn try_from(value: &Value<'_>) -> Result<Self> {
+        // `i64::MAX as f64` rounds up to 2^63, which is out of range, so the end is excluded
+        if value.is_integer() && (i64::MIN as f64..i64::MAX as f64).contains(&value.as_f64()) {
+            Ok(value.as_f64() as i64)
+        } else {
+            Err(Error::I0205WrongType("integer".to_string()))
+        }
+    }
+}
+
+impl TryFrom<&Value<'_>> for bool {
+    type Error = Error;
+
+    fn try_from(value: &Value<'_>) -> Result<Self> {
+        match *value {
+            Value::Bool(b) => Ok(b),
+            _ => Err(Error::I0205WrongType("boolean".to_string())),
+        }
+    }
+}
+
+impl TryFrom<&Value<'_>> for String {
+    type Error = Error;
+
+    fn try_from(value: &Value<'_>) -> Result<Self> {
+        match *value {
+            Value::String(ref s) => Ok(s.clone()),
+            _ => Err(Error::I0205WrongType("string".to_string())),
+        }
+    }
+}
+
+macro_rules! try_from_owned_value {
+    ($($t:ty),*) => {
+        $(
+            impl TryFrom<Value<'_>> for $t {
+                type Error = Error;
+
+                fn try_from(value: Value<'_>) -> Result<Self> {
+                    <$t>::try_from(&value)
+                }
+            }
+        )*
+    };
+}
+
+try_from_owned_value!(f64, i64, bool, String);
+
 impl<'a> Index<&str> for Value<'a> {
     type Output = Value<'a>;
 
@@ -540,7 +679,8 @@ impl std::fmt::Debug for Value<'_> {
             Self::NativeFn0(..)
             | Self::NativeFn1(..)
             | Self::NativeFn2(..)
-            | Self::NativeFn3(..) => {
+            | Self::NativeFn3(..)
+            | Self::NativeFn4(..) => {
                 write!(f, "<nativefn>")
             }
         }